@@ -1,15 +1,39 @@
-use alloc::vec::Vec;
+use alloc::{vec, vec::Vec};
 use casper_contract::contract_api::{
     runtime::{self, revert},
     storage,
 };
-use casper_types::{account::AccountHash, U256};
+use casper_types::{contracts::NamedKeys, Key, U256};
 
-use crate::error::OnlineError;
+use crate::{
+    error::OnlineError,
+    events,
+    online::{item_key, now, read_item, write_item},
+};
+
+// ============================
+// The var in the storage used
+// - accounting: Vec<(Key, U256)> (free token)
+// - checkpoints: dictionary (hash of account -> Vec<(u64, U256)>)
+//   (history of (block time, owned token) of each account, oldest first)
+//
+// Owned token is free token plus token locked in pledges and stakes, only
+// `add` and `reduce` change it, `lock` and `unlock` do not.
 
 // ==========
 // helper functions
-pub fn reduce(account: AccountHash, amount: U256) {
+pub fn reduce(account: Key, amount: U256) {
+    lock(account, amount);
+    checkpoint(account, amount, false);
+}
+
+pub fn add(account: Key, amount: U256) {
+    unlock(account, amount);
+    checkpoint(account, amount, true);
+}
+
+// take free token which is still owned by the account, e.g. for a pledge
+pub fn lock(account: Key, amount: U256) {
     let mut accounting = get_accounting();
     match accounting
         .iter_mut()
@@ -27,7 +51,8 @@ pub fn reduce(account: AccountHash, amount: U256) {
     save_accounting(accounting)
 }
 
-pub fn add(account: AccountHash, amount: U256) {
+// give back token taken by `lock`
+pub fn unlock(account: Key, amount: U256) {
     let mut accounting = get_accounting();
     match accounting
         .iter_mut()
@@ -39,13 +64,69 @@ pub fn add(account: AccountHash, amount: U256) {
     save_accounting(accounting);
 }
 
-pub fn get_accounting() -> Vec<(AccountHash, U256)> {
+// lose token taken by `lock`, e.g. a slashed pledge
+pub fn forfeit(account: Key, amount: U256) {
+    if amount.is_zero() {
+        return;
+    }
+    checkpoint(account, amount, false);
+    events::forfeit(account, amount);
+}
+
+// owned token of an account at the end of the given block time
+pub fn balance_at(account: Key, time: u64) -> U256 {
+    let history = history_of(account);
+    match history.partition_point(|(_time, _)| *_time <= time) {
+        0 => U256::zero(),
+        index => history[index - 1].1,
+    }
+}
+
+// owned token of an account now
+pub fn owned(account: Key) -> U256 {
+    history_of(account)
+        .last()
+        .map(|(_, balance)| *balance)
+        .unwrap_or_default()
+}
+
+fn checkpoint(account: Key, amount: U256, add: bool) {
+    let now = now();
+    let mut history = history_of(account);
+    let (time, last) = history.last().copied().unwrap_or_default();
+    let balance = if add { last + amount } else { last - amount };
+    // one checkpoint per block time is enough
+    if !history.is_empty() && time == now {
+        history.pop();
+    }
+    history.push((now, balance));
+    save_history(account, history);
+}
+
+fn history_of(account: Key) -> Vec<(u64, U256)> {
+    read_item("checkpoints", &item_key(&account)).unwrap_or_default()
+}
+
+fn save_history(account: Key, history: Vec<(u64, U256)>) {
+    write_item("checkpoints", &item_key(&account), history);
+}
+
+// create the checkpoints with the founders owning their token from now on
+pub fn add_checkpoint_keys(keys: &mut NamedKeys, founders: Vec<(Key, U256)>) {
+    let checkpoints = storage::new_dictionary("checkpoints").unwrap();
+    keys.insert("checkpoints".into(), Key::URef(checkpoints));
+    founders
+        .into_iter()
+        .for_each(|(founder, balance)| save_history(founder, vec![(now(), balance)]));
+}
+
+pub fn get_accounting() -> Vec<(Key, U256)> {
     storage::read(runtime::get_key("accounting").unwrap().into_uref().unwrap())
         .unwrap()
         .unwrap()
 }
 
-pub fn save_accounting(accounting: Vec<(AccountHash, U256)>) {
+pub fn save_accounting(accounting: Vec<(Key, U256)>) {
     storage::write(
         runtime::get_key("accounting").unwrap().into_uref().unwrap(),
         accounting,
@@ -0,0 +1,291 @@
+use alloc::{vec, vec::Vec};
+use casper_contract::contract_api::{
+    runtime::{self, revert},
+    storage,
+};
+use casper_types::{
+    contracts::NamedKeys, CLType, EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, Key,
+    Parameter, U256,
+};
+
+use crate::{
+    accounting::{get_accounting, lock, unlock},
+    error::OnlineError,
+    events,
+    online::{caller, circulating, is_holder, mint_to, now, read_key, update_key},
+    ragequit::spent,
+};
+
+// ============================
+// Conviction voting for continuous funding.
+// Members stake tokens on any number of funding proposals at the same time.
+// Every period the conviction of a proposal decays and its current stake is
+// added on top, so conviction grows towards `staked / (1 - decay)` the longer
+// the tokens stay. A proposal is funded from `supply` as soon as its conviction
+// reaches a threshold which grows with the share of `supply` it asks for.
+//
+// The var in the storage used
+// - conviction_proposals: Vec<(u64, (Key, U256), (U256, U256, u64))>
+//   (id, (beneficiary, requested), (staked, conviction, last_update))
+// - conviction_stakes: Vec<(Key, u64, U256)> (account, proposal id, amount)
+// - conviction_next_id: u64
+// - conviction_decay: U256 (per-mille of conviction kept every period)
+// - conviction_period: U256 (period length in milliseconds)
+// - conviction_max_ratio: U256 (per-mille of `supply` one proposal can request)
+// - conviction_weight: U256 (per-mille)
+
+type ConvictionProposal = (u64, (Key, U256), (U256, U256, u64));
+
+// conviction untouched for this many periods has converged anyway
+const MAX_PERIODS: u64 = 256;
+
+#[no_mangle]
+pub extern "C" fn new_conviction_proposal() {
+    // ============
+    // args:
+    // beneficiary: Key
+    // amount: U256
+    // ============
+    let caller = caller();
+    if !get_accounting()
+        .iter()
+        .any(|f| f.0 == caller && f.1 > U256::from(1))
+    {
+        revert(OnlineError::NoPermission)
+    }
+    let beneficiary: Key = runtime::get_named_arg("beneficiary");
+    if !is_holder(&beneficiary) {
+        revert(OnlineError::InvalidRecipient)
+    }
+    let amount: U256 = runtime::get_named_arg("amount");
+    if amount.is_zero() {
+        revert(OnlineError::NoZero)
+    }
+    if threshold(amount).is_none() {
+        revert(OnlineError::RequestTooBig)
+    }
+
+    let id: u64 = read_key("conviction_next_id");
+    update_key("conviction_next_id", id + 1);
+    let mut proposals: Vec<ConvictionProposal> = read_key("conviction_proposals");
+    proposals.push((
+        id,
+        (beneficiary, amount),
+        (U256::zero(), U256::zero(), now()),
+    ));
+    update_key("conviction_proposals", proposals);
+    events::conviction_proposed(id, beneficiary, amount);
+}
+
+#[no_mangle]
+pub extern "C" fn stake_conviction() {
+    // ============
+    // args:
+    // id: u64
+    // amount: U256
+    // ============
+    let account = caller();
+    let id: u64 = runtime::get_named_arg("id");
+    let amount: U256 = runtime::get_named_arg("amount");
+    if amount.is_zero() {
+        revert(OnlineError::NoZero)
+    }
+    let mut proposals: Vec<ConvictionProposal> = read_key("conviction_proposals");
+    match proposals.iter_mut().find(|p| p.0 == id) {
+        Some(proposal) => {
+            accrue(proposal);
+            (proposal.2).0 += amount;
+        }
+        None => revert(OnlineError::NoSuchProposal),
+    }
+    lock(account, amount);
+
+    let mut stakes: Vec<(Key, u64, U256)> = read_key("conviction_stakes");
+    match stakes
+        .iter_mut()
+        .find(|(acc, _id, _)| acc == &account && _id == &id)
+    {
+        Some((_, _, _amount)) => *_amount += amount,
+        None => stakes.push((account, id, amount)),
+    }
+    update_key("conviction_stakes", stakes);
+    events::staked(id, account, amount);
+    settle(proposals, id);
+}
+
+#[no_mangle]
+pub extern "C" fn withdraw_conviction() {
+    // ============
+    // args:
+    // id: u64
+    // amount: U256
+    // ============
+    let account = caller();
+    let id: u64 = runtime::get_named_arg("id");
+    let amount: U256 = runtime::get_named_arg("amount");
+    let mut stakes: Vec<(Key, u64, U256)> = read_key("conviction_stakes");
+    match stakes
+        .iter()
+        .position(|(acc, _id, _amount)| acc == &account && _id == &id && _amount >= &amount)
+    {
+        Some(index) => {
+            stakes[index].2 -= amount;
+            if stakes[index].2.is_zero() {
+                stakes.remove(index);
+            }
+        }
+        None => revert(OnlineError::NoEnoughStake),
+    }
+    update_key("conviction_stakes", stakes);
+    unlock(account, amount);
+    events::unstaked(id, account, amount);
+
+    // the proposal is gone once it has been funded, the stake is just returned then
+    let mut proposals: Vec<ConvictionProposal> = read_key("conviction_proposals");
+    if let Some(proposal) = proposals.iter_mut().find(|p| p.0 == id) {
+        accrue(proposal);
+        (proposal.2).0 -= amount;
+    }
+    settle(proposals, id);
+}
+
+// let anyone push a proposal over the line once time has passed
+#[no_mangle]
+pub extern "C" fn update_conviction() {
+    // ============
+    // args:
+    // id: u64
+    // ============
+    let id: u64 = runtime::get_named_arg("id");
+    let mut proposals: Vec<ConvictionProposal> = read_key("conviction_proposals");
+    match proposals.iter_mut().find(|p| p.0 == id) {
+        Some(proposal) => accrue(proposal),
+        None => revert(OnlineError::NoSuchProposal),
+    }
+    settle(proposals, id);
+}
+
+// bring the conviction of a proposal up to the current block time
+fn accrue(proposal: &mut ConvictionProposal) {
+    let now = now();
+    let period = read_key::<U256>("conviction_period").as_u64().max(1);
+    let decay: U256 = read_key("conviction_decay");
+    let (staked, mut conviction, last_update) = proposal.2;
+    let periods = now.saturating_sub(last_update) / period;
+    for _ in 0..periods.min(MAX_PERIODS) {
+        conviction = conviction * decay / 1000 + staked;
+    }
+    let last_update = if periods > MAX_PERIODS {
+        now
+    } else {
+        last_update + periods * period
+    };
+    proposal.2 = (staked, conviction, last_update);
+}
+
+// fund the proposal if its conviction is high enough, then save the list
+fn settle(mut proposals: Vec<ConvictionProposal>, id: u64) {
+    if let Some(index) = proposals.iter().position(|p| p.0 == id) {
+        let (_, (beneficiary, requested), (_, conviction, _)) = proposals[index];
+        if let Some(threshold) = threshold(requested) {
+            if !conviction.is_zero() && conviction >= threshold {
+                proposals.remove(index);
+                mint_to(beneficiary, requested);
+                spent();
+            }
+        }
+    }
+    update_key("conviction_proposals", proposals);
+}
+
+// conviction needed to fund `requested`:
+// weight * circulating / ((1 - decay) * (max_ratio - requested / supply)^2)
+// None when it asks for too much of the remaining supply.
+fn threshold(requested: U256) -> Option<U256> {
+    let supply: U256 = read_key("supply");
+    let decay: U256 = read_key("conviction_decay");
+    let max_ratio: U256 = read_key("conviction_max_ratio");
+    let weight: U256 = read_key("conviction_weight");
+    if supply.is_zero() || decay >= U256::from(1000) {
+        return None;
+    }
+    let ratio = requested * 1000 / supply;
+    if ratio >= max_ratio {
+        return None;
+    }
+    let gap = max_ratio - ratio;
+    Some(circulating() * weight * 1_000_000 / ((U256::from(1000) - decay) * gap * gap))
+}
+
+pub fn add_conviction_keys(keys: &mut NamedKeys) {
+    let proposals: Vec<ConvictionProposal> = vec![];
+    keys.insert(
+        "conviction_proposals".into(),
+        Key::URef(storage::new_uref(proposals)),
+    );
+    let stakes: Vec<(Key, u64, U256)> = vec![];
+    keys.insert(
+        "conviction_stakes".into(),
+        Key::URef(storage::new_uref(stakes)),
+    );
+    keys.insert(
+        "conviction_next_id".into(),
+        Key::URef(storage::new_uref(0u64)),
+    );
+    keys.insert(
+        "conviction_decay".into(),
+        Key::URef(storage::new_uref(U256::from(900))),
+    );
+    keys.insert(
+        "conviction_period".into(),
+        Key::URef(storage::new_uref(U256::from(3_600_000))),
+    );
+    keys.insert(
+        "conviction_max_ratio".into(),
+        Key::URef(storage::new_uref(U256::from(200))),
+    );
+    keys.insert(
+        "conviction_weight".into(),
+        Key::URef(storage::new_uref(U256::from(2))),
+    );
+}
+
+pub fn add_conviction_entries(entries: &mut EntryPoints) {
+    entries.add_entry_point(EntryPoint::new(
+        "new_conviction_proposal",
+        vec![
+            Parameter::new("beneficiary", CLType::Key),
+            Parameter::new("amount", CLType::U256),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entries.add_entry_point(EntryPoint::new(
+        "stake_conviction",
+        vec![
+            Parameter::new("id", CLType::U64),
+            Parameter::new("amount", CLType::U256),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entries.add_entry_point(EntryPoint::new(
+        "withdraw_conviction",
+        vec![
+            Parameter::new("id", CLType::U64),
+            Parameter::new("amount", CLType::U256),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entries.add_entry_point(EntryPoint::new(
+        "update_conviction",
+        vec![Parameter::new("id", CLType::U64)],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+}
@@ -0,0 +1,237 @@
+use alloc::vec;
+use casper_contract::contract_api::{
+    runtime::{self, revert},
+    storage, system,
+};
+use casper_types::{
+    contracts::NamedKeys, CLType, CLValue, EntryPoint, EntryPointAccess, EntryPointType,
+    EntryPoints, Key, Parameter, URef, U256,
+};
+
+use crate::{
+    accounting::reduce,
+    error::OnlineError,
+    events,
+    online::{caller, mint_to, read_key, update_key},
+    sale::{to_u256, to_u512, treasury},
+};
+
+// ============================
+// Bonding curve.
+// Token is bought from `supply` and sold back to it for CSPR at a price
+// which follows the token issued by the curve:
+// - linear: base + slope * issued
+// - exponential: base * (1 + growth / 10000) ^ (issued / step)
+// Bought CSPR stays in the `treasury` purse and is counted in `curve_reserve`,
+// sells are paid from it. The curve parameters are changed by `update`
+// proposals.
+//
+// The var in the storage used
+// - curve_mode: U256 (0: off, 1: linear, 2: exponential)
+// - curve_base: U256 (motes per token when nothing is issued)
+// - curve_slope: U256 (motes the price grows per issued token, linear)
+// - curve_growth: U256 (basis points the price grows every step, exponential)
+// - curve_step: U256 (token in one step, exponential)
+// - curve_issued: U256 (token issued by the curve and not sold back)
+// - curve_reserve: U256 (motes paid into the treasury by the curve)
+
+// fixed point of the exponential price
+const ONE: u64 = 1_000_000_000_000_000_000;
+// steps one exponential buy or sell can cross
+const MAX_STEPS: u64 = 256;
+
+#[no_mangle]
+pub extern "C" fn buy_on_curve() {
+    // ============
+    // args:
+    // purse: URef (pays the price)
+    // amount: U256
+    // ============
+    let account = caller();
+    let purse: URef = runtime::get_named_arg("purse");
+    let amount: U256 = runtime::get_named_arg("amount");
+    if amount.is_zero() {
+        revert(OnlineError::NoZero)
+    }
+    if amount > read_key("supply") {
+        revert(OnlineError::SupplyTooSmall)
+    }
+    let issued: U256 = read_key("curve_issued");
+    let cost = quote(issued, amount);
+    if to_u256(system::get_purse_balance(purse).unwrap()) < cost {
+        revert(OnlineError::UserHaveNoEnoughToken)
+    }
+    system::transfer_from_purse_to_purse(purse, treasury(), to_u512(cost), None).unwrap();
+    update_key("curve_issued", issued + amount);
+    update_key("curve_reserve", read_key::<U256>("curve_reserve") + cost);
+    mint_to(account, amount);
+}
+
+#[no_mangle]
+pub extern "C" fn sell_on_curve() {
+    // ============
+    // args:
+    // purse: URef (gets the price)
+    // amount: U256
+    // ============
+    let account = caller();
+    let purse: URef = runtime::get_named_arg("purse");
+    let amount: U256 = runtime::get_named_arg("amount");
+    if amount.is_zero() {
+        revert(OnlineError::NoZero)
+    }
+    // only what the curve issued can go back
+    let issued: U256 = read_key("curve_issued");
+    if amount > issued {
+        revert(OnlineError::RequestTooBig)
+    }
+    // rounding may leave the reserve a few motes short of the quote
+    let reserve: U256 = read_key("curve_reserve");
+    let refund = quote(issued - amount, amount).min(reserve);
+    reduce(account, amount);
+    update_key("supply", read_key::<U256>("supply") + amount);
+    update_key("curve_issued", issued - amount);
+    update_key("curve_reserve", reserve - refund);
+    system::transfer_from_purse_to_purse(treasury(), purse, to_u512(refund), None).unwrap();
+    events::curve_sold(account, amount, refund);
+}
+
+// motes to buy `amount` now
+#[no_mangle]
+pub extern "C" fn quote_buy() {
+    // ============
+    // args:
+    // amount: U256
+    // ============
+    let amount: U256 = runtime::get_named_arg("amount");
+    let issued: U256 = read_key("curve_issued");
+    runtime::ret(CLValue::from_t(quote(issued, amount)).unwrap())
+}
+
+// motes selling `amount` pays now
+#[no_mangle]
+pub extern "C" fn quote_sell() {
+    // ============
+    // args:
+    // amount: U256
+    // ============
+    let amount: U256 = runtime::get_named_arg("amount");
+    let issued: U256 = read_key("curve_issued");
+    if amount > issued {
+        revert(OnlineError::RequestTooBig)
+    }
+    runtime::ret(CLValue::from_t(quote(issued - amount, amount)).unwrap())
+}
+
+// price of `amount` token issued after `from`
+fn quote(from: U256, amount: U256) -> U256 {
+    let base: U256 = read_key("curve_base");
+    let mode: U256 = read_key("curve_mode");
+    let cost = if mode == U256::from(1) {
+        // area under the line
+        let slope: U256 = read_key("curve_slope");
+        base.checked_mul(amount).and_then(|flat| {
+            slope
+                .checked_mul(amount)?
+                .checked_mul(from.checked_mul(U256::from(2))?.checked_add(amount)?)
+                .and_then(|rise| flat.checked_add(rise / 2))
+        })
+    } else if mode == U256::from(2) {
+        exponential(base, from, amount)
+    } else {
+        revert(OnlineError::CurveOff)
+    };
+    match cost {
+        Some(cost) => cost,
+        None => revert(OnlineError::RequestTooBig),
+    }
+}
+
+// sum of the price of every step crossed
+fn exponential(base: U256, from: U256, amount: U256) -> Option<U256> {
+    let step = read_key::<U256>("curve_step").max(U256::one());
+    let growth: U256 = read_key("curve_growth");
+    let one = U256::from(ONE);
+    let factor = (U256::from(10000) + growth) * one / 10000;
+    let mut k = from / step;
+    let mut price = base.checked_mul(power(factor, k)?)? / one;
+    let mut at = from;
+    let end = from + amount;
+    let mut cost = U256::zero();
+    for _ in 0..MAX_STEPS {
+        if at >= end {
+            break;
+        }
+        let next = ((k + 1) * step).min(end);
+        cost = cost.checked_add(price.checked_mul(next - at)?)?;
+        at = next;
+        k += U256::one();
+        price = price.checked_mul(factor)? / one;
+    }
+    // too many steps for one call
+    if at < end {
+        return None;
+    }
+    Some(cost)
+}
+
+// factor ^ k in fixed point
+fn power(mut factor: U256, mut k: U256) -> Option<U256> {
+    let one = U256::from(ONE);
+    let mut result = one;
+    while !k.is_zero() {
+        if k.bit(0) {
+            result = result.checked_mul(factor)? / one;
+        }
+        k /= 2;
+        if !k.is_zero() {
+            factor = factor.checked_mul(factor)? / one;
+        }
+    }
+    Some(result)
+}
+
+pub fn add_curve_keys(keys: &mut NamedKeys) {
+    // off, 1 CSPR plus 1000 motes per token when turned on linear,
+    // 1% more every 10000 token when turned on exponential
+    for (name, value) in [
+        ("curve_mode", 0u64),
+        ("curve_base", 1_000_000_000),
+        ("curve_slope", 1000),
+        ("curve_growth", 100),
+        ("curve_step", 10000),
+        ("curve_issued", 0),
+        ("curve_reserve", 0),
+    ]
+    .iter()
+    {
+        keys.insert(
+            (*name).into(),
+            Key::URef(storage::new_uref(U256::from(*value))),
+        );
+    }
+}
+
+pub fn add_curve_entries(entries: &mut EntryPoints) {
+    for name in ["buy_on_curve", "sell_on_curve"].iter() {
+        entries.add_entry_point(EntryPoint::new(
+            *name,
+            vec![
+                Parameter::new("purse", CLType::URef),
+                Parameter::new("amount", CLType::U256),
+            ],
+            CLType::Unit,
+            EntryPointAccess::Public,
+            EntryPointType::Contract,
+        ));
+    }
+    for name in ["quote_buy", "quote_sell"].iter() {
+        entries.add_entry_point(EntryPoint::new(
+            *name,
+            vec![Parameter::new("amount", CLType::U256)],
+            CLType::U256,
+            EntryPointAccess::Public,
+            EntryPointType::Contract,
+        ));
+    }
+}
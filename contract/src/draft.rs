@@ -0,0 +1,234 @@
+use alloc::{string::String, vec, vec::Vec};
+use casper_contract::contract_api::{
+    runtime::{self, revert},
+    storage,
+};
+use casper_types::{
+    contracts::NamedKeys, CLType, EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, Key,
+    Parameter, U256,
+};
+
+use crate::{
+    accounting::{balance_at, forfeit, unlock},
+    error::OnlineError,
+    events,
+    online::{caller, now, open_proposal, read_key, update_key},
+};
+
+// ============================
+// Co-sponsorship of new proposals.
+// `new_proposal` only creates a draft. A draft moves into the proposal slot
+// once enough accounts sponsored it within the window, by count or by the
+// token they owned when the draft was created, whichever is reached first.
+// A draft still waiting when the window is over can be expired by anyone: the
+// creator's vote is returned, the deposit too when the draft was sponsored,
+// otherwise it goes to `supply`. The creator can withdraw its draft any time.
+//
+// The var in the storage used
+// - drafts: Vec<(u64, (Key, String, (U256, U256)), ((bool, U256, U256), (u64, Vec<Key>, U256)))>
+//   (id, (proposer, proposal, (vote_limit, electorate)), ((vote, amount, deposit), (created, sponsors, sponsored token)))
+//   electorate is the circulating supply when the draft was created
+// - sponsor_count: U256 (sponsors needed, 0 disables)
+// - sponsor_balance: U256 (sponsoring token needed, 0 disables)
+// - sponsor_window: U256 (milliseconds a draft can be sponsored)
+
+pub type Draft = (
+    u64,
+    (Key, String, (U256, U256)),
+    ((bool, U256, U256), (u64, Vec<Key>, U256)),
+);
+
+#[no_mangle]
+pub extern "C" fn sponsor_proposal() {
+    // ============
+    // args:
+    // id: u64
+    // ============
+    let caller = caller();
+    let id: u64 = runtime::get_named_arg("id");
+    let mut drafts: Vec<Draft> = read_key("drafts");
+    let index = match drafts.iter().position(|draft| draft.0 == id) {
+        Some(index) => index,
+        None => revert(OnlineError::NoSuchProposal),
+    };
+    let (_, (proposer, _, _), (_, (created, sponsors, sponsored))) = &mut drafts[index];
+    if expired(*created) {
+        revert(OnlineError::DraftExpired)
+    }
+    if *proposer == caller {
+        revert(OnlineError::NoPermission)
+    }
+    if sponsors.contains(&caller) {
+        revert(OnlineError::AlreadyVoted)
+    }
+    // token bought after the draft was created does not count
+    let weight = balance_at(caller, *created);
+    if weight.is_zero() {
+        revert(OnlineError::NoPermission)
+    }
+    sponsors.push(caller);
+    *sponsored += weight;
+    events::sponsored(id, caller);
+
+    if sponsored_enough(&drafts[index]) && slot_free() {
+        open_proposal(drafts.remove(index));
+    }
+    update_key("drafts", drafts);
+}
+
+// move a sponsored draft into the slot once the active proposal is closed
+#[no_mangle]
+pub extern "C" fn activate_proposal() {
+    // ============
+    // args:
+    // id: u64
+    // ============
+    let id: u64 = runtime::get_named_arg("id");
+    let mut drafts: Vec<Draft> = read_key("drafts");
+    let index = match drafts.iter().position(|draft| draft.0 == id) {
+        Some(index) => index,
+        None => revert(OnlineError::NoSuchProposal),
+    };
+    if !sponsored_enough(&drafts[index]) {
+        revert(OnlineError::NotSponsored)
+    }
+    if !slot_free() {
+        revert(OnlineError::HaveUnFinishProposal)
+    }
+    open_proposal(drafts.remove(index));
+    update_key("drafts", drafts);
+}
+
+#[no_mangle]
+pub extern "C" fn expire_draft() {
+    // ============
+    // args:
+    // id: u64
+    // ============
+    let id: u64 = runtime::get_named_arg("id");
+    let mut drafts: Vec<Draft> = read_key("drafts");
+    let index = match drafts.iter().position(|draft| draft.0 == id) {
+        Some(index) => index,
+        None => revert(OnlineError::NoSuchProposal),
+    };
+    let draft = &drafts[index];
+    if !expired(((draft.2).1).0) {
+        revert(OnlineError::NoPermission)
+    }
+    // a sponsored draft which could not be opened is not the creator's fault
+    let sponsored = sponsored_enough(draft);
+    let (_, (proposer, _, _), ((_, amount, deposit), _)) = drafts.remove(index);
+    unlock(proposer, amount);
+    if sponsored {
+        unlock(proposer, deposit);
+    } else if !deposit.is_zero() {
+        forfeit(proposer, deposit);
+        let supply: U256 = read_key("supply");
+        update_key("supply", supply + deposit);
+    }
+    update_key("drafts", drafts);
+    events::draft_removed(id, false);
+}
+
+// the creator takes its draft back with the pledge and the deposit
+#[no_mangle]
+pub extern "C" fn withdraw_draft() {
+    // ============
+    // args:
+    // id: u64
+    // ============
+    let caller = caller();
+    let id: u64 = runtime::get_named_arg("id");
+    let mut drafts: Vec<Draft> = read_key("drafts");
+    let index = match drafts.iter().position(|draft| draft.0 == id) {
+        Some(index) => index,
+        None => revert(OnlineError::NoSuchProposal),
+    };
+    if (drafts[index].1).0 != caller {
+        revert(OnlineError::NoPermission)
+    }
+    let (_, (proposer, _, _), ((_, amount, deposit), _)) = drafts.remove(index);
+    unlock(proposer, amount + deposit);
+    update_key("drafts", drafts);
+    events::draft_removed(id, true);
+}
+
+// called by `new_proposal`, the draft is opened at once when no sponsor is needed
+pub fn add_draft(draft: Draft) {
+    if sponsored_enough(&draft) && slot_free() {
+        open_proposal(draft);
+        return;
+    }
+    let mut drafts: Vec<Draft> = read_key("drafts");
+    drafts.push(draft);
+    update_key("drafts", drafts);
+}
+
+fn sponsored_enough(draft: &Draft) -> bool {
+    let (_, sponsors, sponsored) = &(draft.2).1;
+    let count: U256 = read_key("sponsor_count");
+    let balance: U256 = read_key("sponsor_balance");
+    if count.is_zero() && balance.is_zero() {
+        return true;
+    }
+    (!count.is_zero() && U256::from(sponsors.len()) >= count)
+        || (!balance.is_zero() && *sponsored >= balance)
+}
+
+fn expired(created: u64) -> bool {
+    let window = read_key::<U256>("sponsor_window").as_u64();
+    now() >= created.saturating_add(window)
+}
+
+fn slot_free() -> bool {
+    read_key::<Option<(U256, U256)>>("pool").is_none()
+}
+
+pub fn add_draft_keys(keys: &mut NamedKeys) {
+    let drafts: Vec<Draft> = vec![];
+    keys.insert("drafts".into(), Key::URef(storage::new_uref(drafts)));
+    keys.insert(
+        "sponsor_count".into(),
+        Key::URef(storage::new_uref(U256::from(1))),
+    );
+    keys.insert(
+        "sponsor_balance".into(),
+        Key::URef(storage::new_uref(U256::from(0))),
+    );
+    // three days
+    keys.insert(
+        "sponsor_window".into(),
+        Key::URef(storage::new_uref(U256::from(3 * 24 * 60 * 60 * 1000u64))),
+    );
+}
+
+pub fn add_draft_entries(entries: &mut EntryPoints) {
+    entries.add_entry_point(EntryPoint::new(
+        "sponsor_proposal",
+        vec![Parameter::new("id", CLType::U64)],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entries.add_entry_point(EntryPoint::new(
+        "activate_proposal",
+        vec![Parameter::new("id", CLType::U64)],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entries.add_entry_point(EntryPoint::new(
+        "expire_draft",
+        vec![Parameter::new("id", CLType::U64)],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entries.add_entry_point(EntryPoint::new(
+        "withdraw_draft",
+        vec![Parameter::new("id", CLType::U64)],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+}
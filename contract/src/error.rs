@@ -16,6 +16,7 @@ pub enum PlanError {
     NotOriginal = 0,
     AlreadyHaveProposal = 1,
     NoProposal = 2,
+    InValidPlan = 3,
 }
 
 impl From<PlanError> for ApiError {
@@ -44,6 +45,26 @@ pub enum OnlineError {
     NoZero = 4,
     TooSmall = 5,
     AmountTooBig= 6,
+    RequestTooBig = 7,
+    NoSuchProposal = 8,
+    NoEnoughStake = 9,
+    NotMember = 10,
+    AlreadyVoted = 11,
+    VotingClosed = 12,
+    VotingOpen = 13,
+    OverSnapshot = 14,
+    NothingToClaim = 15,
+    SupplyTooSmall = 16,
+    AlreadyPledged = 17,
+    NotSponsored = 18,
+    DraftExpired = 19,
+    OverVoteShare = 20,
+    InvalidRecipient = 21,
+    SaleClosed = 22,
+    SaleCapReached = 23,
+    CurveOff = 24,
+    ActivePledges = 25,
+    InGracePeriod = 26,
 }
 
 impl From<OnlineError> for ApiError {
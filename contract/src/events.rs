@@ -0,0 +1,279 @@
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use casper_contract::contract_api::{runtime, storage};
+use casper_types::{
+    account::AccountHash,
+    bytesrepr::{Bytes, ToBytes},
+    contracts::NamedKeys,
+    Key, U256,
+};
+
+use crate::online::{read_key, update_key};
+
+// ============================
+// Events in the style of the Casper Event Standard.
+// Every event is the bytes of its name ("event_{Name}") followed by the bytes
+// of its fields in the order of the schema, indexers read them one by one
+// from the `events` dictionary.
+//
+// The var in the storage used
+// - events: dictionary (index -> Bytes)
+// - events_length: u32 (index of the next event)
+// - events_schema: Vec<(String, Vec<(String, String)>)> (event, (field, type))
+
+const SCHEMA: [(&str, &[(&str, &str)]); 21] = [
+    (
+        "Transfer",
+        &[("from", "Key"), ("to", "Key"), ("amount", "U256")],
+    ),
+    ("Mint", &[("recipient", "Key"), ("amount", "U256")]),
+    // owner is None when token outside any balance is burned (reserve, slashed pledges)
+    ("Burn", &[("owner", "Option<Key>"), ("amount", "U256")]),
+    // owned token lost to a slash, a forfeited deposit or a revoked grant
+    ("Forfeit", &[("owner", "Key"), ("amount", "U256")]),
+    ("Join", &[("account", "AccountHash")]),
+    (
+        "PlanProposed",
+        &[("account", "AccountHash"), ("plan", "String")],
+    ),
+    ("PlanVoted", &[("account", "AccountHash"), ("vote", "Bool")]),
+    ("StatusChanged", &[("status", "String")]),
+    (
+        "ProposalCreated",
+        &[("id", "U64"), ("proposer", "Key"), ("proposal", "String")],
+    ),
+    ("Sponsored", &[("id", "U64"), ("sponsor", "Key")]),
+    // the draft moved into the proposal slot, voting is open
+    ("ProposalOpened", &[("id", "U64")]),
+    // withdrawn by its creator, or expired by anyone
+    ("DraftRemoved", &[("id", "U64"), ("withdrawn", "Bool")]),
+    (
+        "Voted",
+        &[
+            ("id", "U64"),
+            ("voter", "Key"),
+            ("vote", "Bool"),
+            ("amount", "U256"),
+        ],
+    ),
+    (
+        "ProposalResolved",
+        &[
+            ("id", "U64"),
+            ("passed", "Bool"),
+            ("executed", "Bool"),
+            ("closed_by", "U8"),
+        ],
+    ),
+    (
+        "RewardPaid",
+        &[("id", "U64"), ("account", "Key"), ("amount", "U256")],
+    ),
+    // token sold back to the reserve on the bonding curve, refund is in motes
+    (
+        "CurveSold",
+        &[("seller", "Key"), ("amount", "U256"), ("refund", "U256")],
+    ),
+    // change is "admit", "expel" or "founder"
+    (
+        "MembershipChanged",
+        &[("change", "String"), ("account", "AccountHash")],
+    ),
+    (
+        "ConvictionProposed",
+        &[("id", "U64"), ("beneficiary", "Key"), ("amount", "U256")],
+    ),
+    (
+        "Staked",
+        &[("id", "U64"), ("account", "Key"), ("amount", "U256")],
+    ),
+    (
+        "Unstaked",
+        &[("id", "U64"), ("account", "Key"), ("amount", "U256")],
+    ),
+    // vested token of a schedule which became free
+    (
+        "Released",
+        &[("id", "U64"), ("beneficiary", "Key"), ("amount", "U256")],
+    ),
+];
+
+// ==========
+// events
+pub fn transfer(from: Key, to: Key, amount: U256) {
+    emit(
+        "Transfer",
+        vec![to_bytes(&from), to_bytes(&to), to_bytes(&amount)],
+    );
+}
+
+pub fn mint(recipient: Key, amount: U256) {
+    emit("Mint", vec![to_bytes(&recipient), to_bytes(&amount)]);
+}
+
+pub fn burn(owner: Option<Key>, amount: U256) {
+    emit("Burn", vec![to_bytes(&owner), to_bytes(&amount)]);
+}
+
+pub fn forfeit(owner: Key, amount: U256) {
+    emit("Forfeit", vec![to_bytes(&owner), to_bytes(&amount)]);
+}
+
+pub fn join(account: AccountHash) {
+    emit("Join", vec![to_bytes(&account)]);
+}
+
+pub fn plan_proposed(account: AccountHash, plan: &str) {
+    emit(
+        "PlanProposed",
+        vec![to_bytes(&account), to_bytes(&plan.to_string())],
+    );
+}
+
+pub fn plan_voted(account: AccountHash, vote: bool) {
+    emit("PlanVoted", vec![to_bytes(&account), to_bytes(&vote)]);
+}
+
+pub fn status_changed(status: &str) {
+    emit("StatusChanged", vec![to_bytes(&status.to_string())]);
+}
+
+pub fn proposal_created(id: u64, proposer: Key, proposal: &str) {
+    emit(
+        "ProposalCreated",
+        vec![
+            to_bytes(&id),
+            to_bytes(&proposer),
+            to_bytes(&proposal.to_string()),
+        ],
+    );
+}
+
+pub fn sponsored(id: u64, sponsor: Key) {
+    emit("Sponsored", vec![to_bytes(&id), to_bytes(&sponsor)]);
+}
+
+pub fn proposal_opened(id: u64) {
+    emit("ProposalOpened", vec![to_bytes(&id)]);
+}
+
+pub fn draft_removed(id: u64, withdrawn: bool) {
+    emit("DraftRemoved", vec![to_bytes(&id), to_bytes(&withdrawn)]);
+}
+
+pub fn voted(id: u64, voter: Key, vote: bool, amount: U256) {
+    emit(
+        "Voted",
+        vec![
+            to_bytes(&id),
+            to_bytes(&voter),
+            to_bytes(&vote),
+            to_bytes(&amount),
+        ],
+    );
+}
+
+pub fn proposal_resolved(id: u64, passed: bool, executed: bool, closed_by: u8) {
+    emit(
+        "ProposalResolved",
+        vec![
+            to_bytes(&id),
+            to_bytes(&passed),
+            to_bytes(&executed),
+            to_bytes(&closed_by),
+        ],
+    );
+}
+
+pub fn reward_paid(id: u64, account: Key, amount: U256) {
+    emit(
+        "RewardPaid",
+        vec![to_bytes(&id), to_bytes(&account), to_bytes(&amount)],
+    );
+}
+
+pub fn curve_sold(seller: Key, amount: U256, refund: U256) {
+    emit(
+        "CurveSold",
+        vec![to_bytes(&seller), to_bytes(&amount), to_bytes(&refund)],
+    );
+}
+
+pub fn membership_changed(change: &str, account: AccountHash) {
+    emit(
+        "MembershipChanged",
+        vec![to_bytes(&change.to_string()), to_bytes(&account)],
+    );
+}
+
+pub fn conviction_proposed(id: u64, beneficiary: Key, amount: U256) {
+    emit(
+        "ConvictionProposed",
+        vec![to_bytes(&id), to_bytes(&beneficiary), to_bytes(&amount)],
+    );
+}
+
+pub fn staked(id: u64, account: Key, amount: U256) {
+    emit(
+        "Staked",
+        vec![to_bytes(&id), to_bytes(&account), to_bytes(&amount)],
+    );
+}
+
+pub fn unstaked(id: u64, account: Key, amount: U256) {
+    emit(
+        "Unstaked",
+        vec![to_bytes(&id), to_bytes(&account), to_bytes(&amount)],
+    );
+}
+
+pub fn released(id: u64, beneficiary: Key, amount: U256) {
+    emit(
+        "Released",
+        vec![to_bytes(&id), to_bytes(&beneficiary), to_bytes(&amount)],
+    );
+}
+
+// ==========
+// helper functions
+fn emit(name: &str, fields: Vec<Vec<u8>>) {
+    let mut event = to_bytes(&["event_", name].concat());
+    fields.into_iter().for_each(|field| event.extend(field));
+    let index: u32 = read_key("events_length");
+    let events = runtime::get_key("events").unwrap().into_uref().unwrap();
+    storage::dictionary_put(events, &index.to_string(), Bytes::from(event));
+    update_key("events_length", index + 1);
+}
+
+fn to_bytes<T: ToBytes + ?Sized>(value: &T) -> Vec<u8> {
+    value.to_bytes().unwrap()
+}
+
+// create the event keys in the installing account, called once by `call`
+pub fn init_events() {
+    storage::new_dictionary("events").unwrap();
+    runtime::put_key("events_length", Key::URef(storage::new_uref(0u32)));
+    let schema: Vec<(String, Vec<(String, String)>)> = SCHEMA
+        .iter()
+        .map(|(name, fields)| {
+            (
+                name.to_string(),
+                fields
+                    .iter()
+                    .map(|(field, ty)| (field.to_string(), ty.to_string()))
+                    .collect(),
+            )
+        })
+        .collect();
+    runtime::put_key("events_schema", Key::URef(storage::new_uref(schema)));
+}
+
+// share the event keys with a contract
+pub fn add_event_keys(keys: &mut NamedKeys) {
+    for name in ["events", "events_length", "events_schema"].iter() {
+        keys.insert(name.to_string(), runtime::get_key(name).unwrap());
+    }
+}
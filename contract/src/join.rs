@@ -5,7 +5,7 @@ use casper_contract::contract_api::{
 };
 use casper_types::account::AccountHash;
 
-use crate::{error::CreateError, gardian};
+use crate::{error::CreateError, events, gardian};
 
 #[no_mangle]
 pub extern "C" fn join() {
@@ -19,6 +19,7 @@ pub extern "C" fn join() {
         revert(CreateError::AlreadyJoin)
     };
     originals.push(caller);
+    events::join(caller);
     let len = originals.len();
     storage::write(
         runtime::get_key("originals")
@@ -38,4 +39,5 @@ fn join_to_plan() {
         runtime::get_key("status").unwrap().into_uref().unwrap(),
         "plan".to_string(),
     );
+    events::status_changed("plan");
 }
@@ -8,10 +8,20 @@ compile_error!("target arch should be wasm32: compile with '--target wasm32-unkn
 // `no_std` environment.
 extern crate alloc;
 mod accounting;
+mod conviction;
+mod curve;
+mod draft;
 mod error;
+mod events;
 mod join;
+mod membership;
 mod online;
 mod plan;
+mod query;
+mod ragequit;
+mod rules;
+mod sale;
+mod vesting;
 
 use alloc::{
     string::{String, ToString},
@@ -28,7 +38,10 @@ use casper_types::{
     EntryPointType, EntryPoints, Key, Parameter, U256,
 };
 
-use crate::online::online_entries;
+use crate::{
+    events::{self, add_event_keys, init_events},
+    online::online_entries,
+};
 
 #[no_mangle]
 pub extern "C" fn call() {
@@ -38,6 +51,7 @@ pub extern "C" fn call() {
     // - status: String ( join | plan | online )
     // - DAO_contract_hash: ContractHash
     // - plan: String (format "{supply}")
+    // - events, events_length, events_schema (see events.rs)
 
     // Parse DAO name
     let name: String = runtime::get_named_arg("name");
@@ -64,10 +78,13 @@ pub extern "C" fn call() {
     runtime::put_key("plan", Key::URef(name_uref));
 
     // Accounting
-    let accounting: Vec<(AccountHash, U256)> = vec![];
+    let accounting: Vec<(Key, U256)> = vec![];
     let accounting_uref = storage::new_uref(accounting);
     runtime::put_key("accounting", Key::URef(accounting_uref));
 
+    // events
+    init_events();
+
     // update contract
     let mut keys = NamedKeys::new();
     keys.insert("name".into(), runtime::get_key("name").unwrap());
@@ -79,6 +96,7 @@ pub extern "C" fn call() {
         "DAO_contract_hash".into(),
         runtime::get_key("DAO_contract_hash").unwrap(),
     );
+    add_event_keys(&mut keys);
 
     let mut entries = online_entries();
     add_join_entry(&mut entries);
@@ -100,6 +118,7 @@ pub extern "C" fn call() {
         runtime::get_key("status").unwrap().into_uref().unwrap(),
         "join".to_string(),
     );
+    events::status_changed("join");
 }
 
 fn add_join_entry(entries: &mut EntryPoints) {
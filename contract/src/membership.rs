@@ -0,0 +1,242 @@
+use alloc::{string::String, vec, vec::Vec};
+use casper_contract::contract_api::{
+    runtime::{self, revert},
+    storage,
+};
+use casper_types::{
+    account::AccountHash, contracts::NamedKeys, CLType, EntryPoint, EntryPointAccess,
+    EntryPointType, EntryPoints, Key, Parameter, U256,
+};
+
+use crate::{
+    error::OnlineError,
+    events,
+    online::{caller, now, read_key, update_key},
+};
+
+// ============================
+// One-member-one-vote decisions.
+// Membership is not token weighted: every registered member has exactly one
+// vote on a member proposal, whatever is in `accounting`.
+//
+// Member proposal format:
+// - "admit {account-hash}"   add a member
+// - "expel {account-hash}"   remove a member (and its founder seat)
+// - "founder {account-hash}" give a member a founder seat in `originals`
+//
+// The var in the storage used
+// - originals: Vec<AccountHash>
+// - members: Vec<AccountHash> (originals plus admitted members)
+// - member_proposal: Option<String>
+// - member_votes: Option<Vec<(AccountHash, bool)>>
+// - member_deadline: Option<u64> (block time the vote on the member proposal ends)
+// - member_quorum: U256 (per-mille of members who have to vote)
+// - member_period: U256 (milliseconds members can vote on a member proposal)
+
+#[no_mangle]
+pub extern "C" fn new_member_proposal() {
+    // ============
+    // args:
+    // proposal: String
+    // ============
+    let caller = member_caller();
+    if read_key::<Option<String>>("member_proposal").is_some() {
+        revert(OnlineError::HaveUnFinishProposal)
+    }
+    let proposal: String = runtime::get_named_arg("proposal");
+    if parse(&proposal).is_none() {
+        revert(OnlineError::InValidProposal)
+    }
+    update_key("member_proposal", Some(proposal));
+    // the proposer is counted as an upvote
+    update_key("member_votes", Some(vec![(caller, true)]));
+    let period = read_key::<U256>("member_period").as_u64();
+    update_key("member_deadline", Some(now().saturating_add(period)));
+    if decided() {
+        resolve();
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn vote_as_member() {
+    // ============
+    // args:
+    // vote: bool
+    // ============
+    let caller = member_caller();
+    let mut votes = match read_key::<Option<Vec<(AccountHash, bool)>>>("member_votes") {
+        Some(votes) => votes,
+        None => revert(OnlineError::NoSuchProposal),
+    };
+    if voting_over() {
+        revert(OnlineError::VotingClosed)
+    }
+    if votes.iter().any(|(member, _)| member == &caller) {
+        revert(OnlineError::AlreadyVoted)
+    }
+    let vote: bool = runtime::get_named_arg("vote");
+    votes.push((caller, vote));
+    update_key("member_votes", Some(votes));
+    if decided() {
+        resolve();
+    }
+}
+
+// close the member proposal after its deadline, or before when it is decided
+#[no_mangle]
+pub extern "C" fn finalize_member_proposal() {
+    if read_key::<Option<String>>("member_proposal").is_none() {
+        revert(OnlineError::NoSuchProposal)
+    }
+    if !voting_over() && !decided() {
+        revert(OnlineError::VotingOpen)
+    }
+    resolve();
+}
+
+fn voting_over() -> bool {
+    now() >= read_key::<Option<u64>>("member_deadline").unwrap()
+}
+
+// (upvote, downvote, members who have not voted, votes needed)
+fn tally() -> (usize, usize, usize, usize) {
+    let votes: Vec<(AccountHash, bool)> = read_key::<Option<_>>("member_votes").unwrap();
+    let members = read_key::<Vec<AccountHash>>("members").len();
+    let quorum = read_key::<U256>("member_quorum").as_usize();
+    let upvote = votes.iter().filter(|(_, vote)| *vote).count();
+    // round up, at least one vote is needed
+    let needed = ((members * quorum + 999) / 1000).max(1);
+    (
+        upvote,
+        votes.len() - upvote,
+        members.saturating_sub(votes.len()),
+        needed,
+    )
+}
+
+// whether the members who have not voted yet can still change the result
+fn decided() -> bool {
+    let (upvote, downvote, rest, needed) = tally();
+    let passes = upvote + downvote >= needed && upvote > downvote + rest;
+    let fails = upvote + rest <= downvote || upvote + downvote + rest < needed;
+    passes || fails
+}
+
+// passed with the quorum and more upvote than downvote
+fn resolve() {
+    let (upvote, downvote, _, needed) = tally();
+    if upvote + downvote >= needed && upvote > downvote {
+        let proposal: String = read_key::<Option<_>>("member_proposal").unwrap();
+        execute(&proposal);
+    }
+    update_key::<Option<String>>("member_proposal", None);
+    update_key::<Option<Vec<(AccountHash, bool)>>>("member_votes", None);
+    update_key::<Option<u64>>("member_deadline", None);
+}
+
+fn execute(proposal: &str) {
+    let (method, account) = parse(proposal).unwrap();
+    let mut members: Vec<AccountHash> = read_key("members");
+    let mut originals: Vec<AccountHash> = read_key("originals");
+    let before = (members.len(), originals.len());
+    match method {
+        "admit" => {
+            if !members.contains(&account) {
+                members.push(account)
+            }
+        }
+        "expel" => {
+            members.retain(|member| member != &account);
+            originals.retain(|original| original != &account);
+        }
+        "founder" => {
+            if members.contains(&account) && !originals.contains(&account) {
+                originals.push(account)
+            }
+        }
+        _ => {}
+    }
+    // admitting a member or seating a founder twice changes nothing
+    if before == (members.len(), originals.len()) {
+        return;
+    }
+    update_key("members", members);
+    update_key("originals", originals);
+    events::membership_changed(method, account);
+}
+
+fn parse(proposal: &str) -> Option<(&str, AccountHash)> {
+    let mut s = proposal.split_ascii_whitespace();
+    let method = s.next()?;
+    if !["admit", "expel", "founder"].contains(&method) {
+        return None;
+    }
+    let account = AccountHash::from_formatted_str(s.next()?).ok()?;
+    Some((method, account))
+}
+
+// the member calling the DAO itself, a contract it calls can not vote for it
+fn member_caller() -> AccountHash {
+    let account = match caller() {
+        Key::Account(account) => account,
+        _ => revert(OnlineError::NotMember),
+    };
+    if !read_key::<Vec<AccountHash>>("members").contains(&account) {
+        revert(OnlineError::NotMember)
+    }
+    account
+}
+
+pub fn add_membership_keys(keys: &mut NamedKeys) {
+    let originals = runtime::get_key("originals").unwrap();
+    let members: Vec<AccountHash> = storage::read(originals.into_uref().unwrap())
+        .unwrap()
+        .unwrap();
+    keys.insert("originals".into(), originals);
+    keys.insert("members".into(), Key::URef(storage::new_uref(members)));
+    keys.insert(
+        "member_proposal".into(),
+        Key::URef(storage::new_uref::<Option<String>>(None)),
+    );
+    keys.insert(
+        "member_votes".into(),
+        Key::URef(storage::new_uref::<Option<Vec<(AccountHash, bool)>>>(None)),
+    );
+    keys.insert(
+        "member_deadline".into(),
+        Key::URef(storage::new_uref::<Option<u64>>(None)),
+    );
+    keys.insert(
+        "member_quorum".into(),
+        Key::URef(storage::new_uref(U256::from(500))),
+    );
+    // three days
+    keys.insert(
+        "member_period".into(),
+        Key::URef(storage::new_uref(U256::from(3 * 24 * 60 * 60 * 1000u64))),
+    );
+}
+
+pub fn add_membership_entries(entries: &mut EntryPoints) {
+    entries.add_entry_point(EntryPoint::new(
+        "new_member_proposal",
+        vec![Parameter::new("proposal", CLType::String)],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entries.add_entry_point(EntryPoint::new(
+        "vote_as_member",
+        vec![Parameter::new("vote", CLType::Bool)],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entries.add_entry_point(EntryPoint::new(
+        "finalize_member_proposal",
+        vec![],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+}
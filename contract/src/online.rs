@@ -1,34 +1,132 @@
 use core::ops::Div;
 
 use crate::{
-    accounting::{add, get_accounting, reduce},
+    accounting::{add, balance_at, forfeit, get_accounting, lock, reduce, unlock},
+    conviction::add_conviction_entries,
+    curve::add_curve_entries,
+    draft::{add_draft, add_draft_entries, Draft},
     error::OnlineError,
+    events,
+    membership::add_membership_entries,
+    query::add_query_entries,
+    ragequit::{add_ragequit_entries, record_spend},
+    rules::{approval_of, approved, parse_rule, period_of, quorum_of, set_rule},
+    sale::{add_sale_entries, execute_sale},
+    vesting::{
+        add_vesting_entries, create_vesting, parse_vesting, pledge_locked, return_pledged, revoke,
+    },
 };
 use alloc::{
+    format,
     string::{String, ToString},
     vec,
     vec::Vec,
 };
 use casper_contract::contract_api::{
-    runtime::{self, get_caller, revert},
+    runtime::{self, revert},
     storage,
 };
 use casper_types::{
-    account::AccountHash, CLType, EntryPoint, EntryPoints, Parameter, PublicKey, U256,
+    system::CallStackElement, CLType, CLValue, EntryPoint, EntryPoints, Key, Parameter, U256,
 };
 
 // ============================
 // The var in the storage used
 // - supply: U256
-// - reward: U256
-// - accounting: Vec<(AccountHash, U256)>
-// - pledges: Option<Vec<(AccountHash, U256)>>
+// - reward: U256 (reward pool of each proposal, shared by the winning pledges)
+// - accounting: Vec<(Key, U256)> (Key::Account or Key::Hash of a contract package)
+// - pledges: dictionary (hash of (proposal id, account) -> (U256, U256))
+//   (unclaimed upvote and downvote pledges of an account on a proposal)
+// - pledged_proposals: dictionary (hash of account -> Vec<u64>)
+//   (proposals the account has unclaimed pledges on)
+// - results: dictionary (proposal id -> ((bool, bool, u8), ((U256, U256), (U256, U256)), bool))
+//   ((passed, executed, closed by), ((unclaimed upvote, unclaimed downvote), (reward left, refund left)),
+//   slashed token is burned when the losers claim)
+// - guardian: Option<Key> (can cancel any proposal)
+// - proposal_deposit: U256 (locked from the proposer by `new_proposal`)
+// - deposit_margin: U256 (basis points of downvote which forfeit the deposit)
+// - deposit: Option<(Key, U256)> (proposer and deposit of the active proposal)
+// - slash_rate: U256 (basis points of a losing pledge which is slashed)
+// - max_vote_share: U256 (basis points of vote_limit one account can pledge on a proposal)
+// - slash_target: U256 (where slashed token goes, 0: winners, 1: supply, 2: burned)
+// - next_proposal_id: u64
+// - proposal_id: Option<u64>
 // - pool: Option<(U256,U256)> (upvote,downvote)
 // - vote_limit: Option<U256>
+// - min_vote_limit: U256 (basis points of the circulating supply vote_limit has to reach)
 // - proposal: Option<String>
+// - deadline: Option<u64> (block time the vote of the proposal ends)
+// - snapshot: Option<u64> (block time voting power of the proposal is read at)
+// - electorate: Option<U256> (circulating supply at the snapshot, most token which can pledge)
+// - checkpoints (see accounting.rs)
+// - total_supply: U256 (reserve plus everything handed out)
+// - total_burned: U256 (everything destroyed, from holders or the reserve)
+// - proposal_rules, quorum, approval (see rules.rs)
+// - drafts (see draft.rs)
+// - vestings (see vesting.rs)
+// - treasury, sale_* (see sale.rs)
+// - curve_* (see curve.rs)
+// - ragequit_grace, last_spend (see ragequit.rs)
 
-fn mint_to(account: AccountHash, amount: U256) {
+// proposal methods
+const METHODS: [&str; 8] = [
+    "mint", "burn", "update", "rule", "guardian", "vesting", "revoke", "sale",
+];
+
+// why a proposal was closed
+const CLOSED_BY_LIMIT: u8 = 0;
+const CLOSED_BY_DEADLINE: u8 = 1;
+const CANCELLED: u8 = 2;
+const CLOSED_EARLY: u8 = 3;
+
+// U256 values which can be changed by an `update` proposal
+const UPDATABLE: [&str; 28] = [
+    "reward",
+    "max_vote_share",
+    "vesting_votes",
+    "sale_price",
+    "sale_account_cap",
+    "sale_cap",
+    "sale_start",
+    "sale_end",
+    "curve_mode",
+    "curve_base",
+    "curve_slope",
+    "curve_growth",
+    "curve_step",
+    "ragequit_grace",
+    "min_vote_limit",
+    "proposal_deposit",
+    "deposit_margin",
+    "slash_rate",
+    "slash_target",
+    "member_quorum",
+    "member_period",
+    "conviction_decay",
+    "conviction_period",
+    "conviction_max_ratio",
+    "conviction_weight",
+    "sponsor_count",
+    "sponsor_balance",
+    "sponsor_window",
+];
+
+// updatable keys read as milliseconds, they have to fit in u64
+const TIME_SPANS: [&str; 4] = [
+    "ragequit_grace",
+    "member_period",
+    "conviction_period",
+    "sponsor_window",
+];
+
+// token held by members, everything handed out of the reserve
+pub fn circulating() -> U256 {
+    read_key::<U256>("total_supply") - read_key::<U256>("supply")
+}
+
+pub fn mint_to(account: Key, amount: U256) {
     add(account, amount);
+    events::mint(account, amount);
     let mut supply: U256 = storage::read(runtime::get_key("supply").unwrap().into_uref().unwrap())
         .unwrap()
         .unwrap();
@@ -39,54 +137,237 @@ fn mint_to(account: AccountHash, amount: U256) {
     )
 }
 
-// pledge token for vote
-fn pledges(account: AccountHash, amount: U256, vote: bool) {
-    reduce(account, amount);
-    if let Some(mut pledges) = read_key::<Option<Vec<(AccountHash, U256, bool)>>>("pledges") {
-        match pledges
-            .iter_mut()
-            .find(|(acc, _, _bool)| acc == &account && _bool == &vote)
-        {
-            Some((_account, _amount, _)) => *_amount += amount,
-            None => pledges.push((account, amount, vote)),
+type Outcome = ((bool, bool, u8), ((U256, U256), (U256, U256)), bool);
+
+// pledge token for vote on the active proposal
+// free token is taken first, then token locked in vesting schedules
+fn pledges(account: Key, amount: U256, vote: bool) {
+    let free = get_accounting()
+        .into_iter()
+        .find(|(_account, _)| _account == &account)
+        .map(|(_, free)| free)
+        .unwrap_or_default();
+    if free < amount {
+        let id: u64 = read_key::<Option<_>>("proposal_id").unwrap();
+        if !free.is_zero() {
+            lock(account, free);
         }
-        update_key("pledges", Some(pledges))
+        pledge_locked(id, account, amount - free);
+    } else {
+        lock(account, amount);
+    }
+    record_pledge(account, amount, vote);
+}
+
+// save a pledge of locked token
+// an account can not pledge more than it owned when the proposal was opened
+fn record_pledge(account: Key, amount: U256, vote: bool) {
+    let id: u64 = read_key::<Option<_>>("proposal_id").unwrap();
+    let (upvote, downvote) = pledge_of(id, account);
+    let pledged = upvote + downvote + amount;
+    let snapshot: u64 = read_key::<Option<_>>("snapshot").unwrap();
+    if pledged > balance_at(account, snapshot) {
+        revert(OnlineError::OverSnapshot)
+    }
+    // no single account decides a proposal alone
+    let vote_limit: U256 = read_key::<Option<_>>("vote_limit").unwrap();
+    if pledged * 10000 > vote_limit * read_key::<U256>("max_vote_share") {
+        revert(OnlineError::OverVoteShare)
+    }
+    if upvote.is_zero() && downvote.is_zero() {
+        let mut proposals = pledged_proposals(account);
+        proposals.push(id);
+        write_item("pledged_proposals", &item_key(&account), proposals);
+    }
+    let pledge = if vote {
+        (upvote + amount, downvote)
+    } else {
+        (upvote, downvote + amount)
     };
+    write_item("pledges", &item_key(&(id, account)), pledge);
+    events::voted(id, account, vote, amount);
+}
+
+// (upvote, downvote) an account pledged on a proposal and has not claimed
+fn pledge_of(id: u64, account: Key) -> (U256, U256) {
+    read_item("pledges", &item_key(&(id, account))).unwrap_or_default()
+}
+
+// proposals an account has unclaimed pledges on
+pub fn pledged_proposals(account: Key) -> Vec<u64> {
+    read_item("pledged_proposals", &item_key(&account)).unwrap_or_default()
 }
-fn pledges_back(result: bool) {
-    let pledges: Vec<(AccountHash, U256, bool)> = read_key::<Option<_>>("pledges").unwrap();
-    pledges.into_iter().map(|(account, amount, vote)| {
-        add(account, amount);
-        if vote == result {
-            let reward: U256 =
-                storage::read(runtime::get_key("reward").unwrap().into_uref().unwrap())
-                    .unwrap()
-                    .unwrap();
-
-            mint_to(account, reward);
+
+// take back the pledges on a closed proposal
+// the winning side shares the reward pool of the proposal pro-rata,
+// the losing side shares what is left of its pledges after slashing
+#[no_mangle]
+pub extern "C" fn claim() {
+    // ============
+    // args:
+    // id: u64
+    // ============
+    let account = caller();
+    let id: u64 = runtime::get_named_arg("id");
+    let (outcome, ((upvote_left, downvote_left), (mut reward_left, mut refund_left)), burn) =
+        match read_item::<Outcome>("results", &id.to_string()) {
+            Some(outcome) => outcome,
+            None => revert(OnlineError::NoSuchProposal),
+        };
+    let (upvote, downvote) = pledge_of(id, account);
+    if upvote.is_zero() && downvote.is_zero() {
+        revert(OnlineError::NothingToClaim)
+    }
+    let result = outcome.0;
+    let (winning_left, losing_left, winning_mine, losing_mine) = if result {
+        (upvote_left, downvote_left, upvote, downvote)
+    } else {
+        (downvote_left, upvote_left, downvote, upvote)
+    };
+    let mut returned = winning_mine;
+    // shares are taken from what is left, so the last claimer gets the rounding dust
+    if !winning_mine.is_zero() {
+        let reward = reward_left * winning_mine / winning_left;
+        unlock(account, winning_mine);
+        add(account, reward);
+        reward_left -= reward;
+        events::reward_paid(id, account, reward);
+    }
+    if !losing_mine.is_zero() {
+        let refund = refund_left * losing_mine / losing_left;
+        let slashed = losing_mine - refund;
+        unlock(account, refund);
+        forfeit(account, slashed);
+        // burned only now, the slashed token was owned by the loser until this claim
+        if burn && !slashed.is_zero() {
+            burned(slashed);
+            events::burn(None, slashed);
         }
-    }).count();
+        refund_left -= refund;
+        returned += refund;
+    }
+    return_pledged(id, account, returned);
+    // claimed pledges are cleared, so nothing can be claimed twice
+    write_item(
+        "pledges",
+        &item_key(&(id, account)),
+        (U256::zero(), U256::zero()),
+    );
+    let mut proposals = pledged_proposals(account);
+    proposals.retain(|_id| _id != &id);
+    write_item("pledged_proposals", &item_key(&account), proposals);
+    let left = (upvote_left - upvote, downvote_left - downvote);
+    write_item(
+        "results",
+        &id.to_string(),
+        (outcome, (left, (reward_left, refund_left)), burn),
+    );
 }
-fn execute() {
-    let order: String = read_key::<Option<_>>("proposal").unwrap();
+
+// run a passed proposal
+// a failure is returned instead of reverting, so the tally is kept
+fn execute(order: &str) -> Result<(), OnlineError> {
     let mut s = order.split_ascii_whitespace();
-    let method = s.next().unwrap().to_string();
-    if &method == "mint" {
-        let amount = s.next().unwrap().to_string().parse::<U256>().unwrap();
-        let account = AccountHash::from_formatted_str(s.next().unwrap()).unwrap();
+    let method = s.next().ok_or(OnlineError::InValidProposal)?;
+    if method == "mint" {
+        let amount = s
+            .next()
+            .and_then(|f| U256::from_dec_str(f).ok())
+            .ok_or(OnlineError::InValidProposal)?;
+        let account = s
+            .next()
+            .and_then(|f| Key::from_formatted_str(f).ok())
+            .filter(is_holder)
+            .ok_or(OnlineError::InValidProposal)?;
+        if amount > read_key::<U256>("supply") {
+            return Err(OnlineError::SupplyTooSmall);
+        }
         mint_to(account, amount);
-        drop(account);
-    } else if &method == "update" {
-        if "reward" == s.next().unwrap() {
-            let reward = U256::from(s.next().unwrap().to_string().parse::<usize>().unwrap());
-            storage::write(
-                runtime::get_key("reward").unwrap().into_uref().unwrap(),
-                reward,
-            );
+    } else if method == "burn" {
+        // destroy part of the reserve
+        let amount = s
+            .next()
+            .and_then(|f| U256::from_dec_str(f).ok())
+            .ok_or(OnlineError::InValidProposal)?;
+        if amount > read_key::<U256>("supply") {
+            return Err(OnlineError::SupplyTooSmall);
         }
+        update_key("supply", read_key::<U256>("supply") - amount);
+        burned(amount);
+        events::burn(None, amount);
+    } else if method == "update" {
+        let key = s
+            .next()
+            .filter(|key| UPDATABLE.contains(key))
+            .ok_or(OnlineError::InValidProposal)?;
+        let value = s
+            .next()
+            .and_then(|f| U256::from_dec_str(f).ok())
+            .filter(|value| in_range(key, *value))
+            .ok_or(OnlineError::InValidProposal)?;
+        update_key(key, value);
         // and so on
+    } else if method == "rule" {
+        set_rule(parse_rule(order).ok_or(OnlineError::InValidProposal)?);
+    } else if method == "guardian" {
+        let guardian = match s.next().ok_or(OnlineError::InValidProposal)? {
+            "none" => None,
+            f => Some(Key::from_formatted_str(f).map_err(|_| OnlineError::InValidProposal)?),
+        };
+        update_key("guardian", guardian);
+    } else if method == "vesting" {
+        let (beneficiary, amount, time, revocable) =
+            parse_vesting(order).ok_or(OnlineError::InValidProposal)?;
+        create_vesting(beneficiary, amount, time, revocable)?;
+    } else if method == "revoke" {
+        let id = s
+            .next()
+            .and_then(|f| f.parse::<u64>().ok())
+            .ok_or(OnlineError::InValidProposal)?;
+        revoke(id)?;
+    } else if method == "sale" {
+        execute_sale(order)?;
     }
     // and so on
+    Ok(())
+}
+
+// whether `update` can set the key to the value without breaking the code reading it
+fn in_range(key: &str, value: U256) -> bool {
+    if TIME_SPANS.contains(&key) {
+        value <= U256::from(u64::MAX)
+    } else if key == "max_vote_share" {
+        // basis points, 0 would stop every pledge
+        !value.is_zero() && value <= U256::from(10000)
+    } else if key == "member_quorum" {
+        // per-mille
+        value <= U256::from(1000)
+    } else {
+        true
+    }
+}
+
+// destroy own token
+#[no_mangle]
+pub extern "C" fn burn() {
+    // ============
+    // args:
+    // amount: U256
+    // ============
+    let account = caller();
+    let amount: U256 = runtime::get_named_arg("amount");
+    if amount.is_zero() {
+        revert(OnlineError::NoZero)
+    }
+    reduce(account, amount);
+    burned(amount);
+    events::burn(Some(account), amount);
+}
+
+// token which left the total supply for good
+pub fn burned(amount: U256) {
+    update_key("total_supply", read_key::<U256>("total_supply") - amount);
+    update_key("total_burned", read_key::<U256>("total_burned") + amount);
 }
 
 #[no_mangle]
@@ -94,13 +375,17 @@ pub extern "C" fn transfer() {
     // ============
     // args:
     // amount: U256
-    // recipient: AccountHash
+    // recipient: Key
     // ============
-    let from: AccountHash = runtime::get_caller();
-    let to: AccountHash = runtime::get_named_arg::<PublicKey>("recipient").to_account_hash();
+    let from = caller();
+    let to: Key = runtime::get_named_arg("recipient");
+    if !is_holder(&to) {
+        revert(OnlineError::InvalidRecipient)
+    }
     let amount: U256 = runtime::get_named_arg("amount");
     reduce(from, amount);
     add(to, amount);
+    events::transfer(from, to, amount);
 }
 
 // vote
@@ -111,36 +396,212 @@ pub extern "C" fn vote_by_pledges() {
     // amount: U256
     // vote: bool
     // ============
-    let account = runtime::get_caller();
+    let account = caller();
     let amount: U256 = runtime::get_named_arg("amount");
     if amount < U256::from(1) {
         revert(OnlineError::NoZero)
     }
     let vote: bool = runtime::get_named_arg("vote");
-    let vote_limit: U256 = read_key::<Option<_>>("vote_limit").unwrap();
+    let mut pool: (U256, U256) = match read_key::<Option<_>>("pool") {
+        Some(pool) => pool,
+        None => revert(OnlineError::NoSuchProposal),
+    };
+    if voting_closed(pool) {
+        revert(OnlineError::VotingClosed)
+    }
 
     pledges(account, amount, vote);
-    let mut pool: (U256, U256) = read_key::<Option<_>>("pool").unwrap();
-
     if vote {
         pool = (pool.0 + amount, pool.1);
     } else {
         pool = (pool.0, pool.1 + amount);
     }
-    if pool.0 + pool.1 >= vote_limit {
-        update_key::<Option<(U256, U256)>>("pool", None);
-        if pool.0 > pool.1 {
-            execute()
+    storage::write(
+        runtime::get_key("pool").unwrap().into_uref().unwrap(),
+        Some(pool),
+    );
+    // no need to wait for `finalize_proposal` when the rest can not flip it
+    if decided(pool) {
+        resolve(pool, CLOSED_EARLY);
+    }
+}
+
+// whether the result stays the same whatever the unpledged token does
+// upvotes only help a proposal, downvotes only hurt one which reached quorum
+fn decided(pool: (U256, U256)) -> bool {
+    let quorum: U256 = read_key::<Option<_>>("quorum").unwrap();
+    let approval: (U256, bool) = read_key::<Option<_>>("approval").unwrap();
+    let electorate: U256 = read_key::<Option<_>>("electorate").unwrap();
+    let rest = electorate.saturating_sub(pool.0 + pool.1);
+    if approved(pool, quorum, approval) {
+        approved((pool.0, pool.1 + rest), quorum, approval)
+    } else {
+        !approved((pool.0 + rest, pool.1), quorum, approval)
+    }
+}
+
+// vote is over once vote_limit is hit or the voting period ends
+fn voting_closed(pool: (U256, U256)) -> bool {
+    let vote_limit: U256 = read_key::<Option<_>>("vote_limit").unwrap();
+    let deadline: u64 = read_key::<Option<_>>("deadline").unwrap();
+    pool.0 + pool.1 >= vote_limit || now() >= deadline
+}
+
+// judge the final tally, run the proposal and free the slot
+#[no_mangle]
+pub extern "C" fn finalize_proposal() {
+    let pool: (U256, U256) = match read_key::<Option<_>>("pool") {
+        Some(pool) => pool,
+        None => revert(OnlineError::NoSuchProposal),
+    };
+    if !voting_closed(pool) {
+        revert(OnlineError::VotingOpen)
+    }
+    let vote_limit: U256 = read_key::<Option<_>>("vote_limit").unwrap();
+    let closed_by = if pool.0 + pool.1 >= vote_limit {
+        CLOSED_BY_LIMIT
+    } else {
+        CLOSED_BY_DEADLINE
+    };
+    resolve(pool, closed_by);
+}
+
+fn resolve(pool: (U256, U256), closed_by: u8) {
+    update_key::<Option<(U256, U256)>>("pool", None);
+    let proposal: String = read_key::<Option<_>>("proposal").unwrap();
+    let kind = proposal.split_ascii_whitespace().next().unwrap();
+    let quorum: U256 = read_key::<Option<_>>("quorum").unwrap();
+    let approval: (U256, bool) = read_key::<Option<_>>("approval").unwrap();
+    let passed = approved(pool, quorum, approval);
+    // read before running, so an `update reward` proposal does not pay its own new value
+    let reward: U256 = read_key("reward");
+    let executed = passed && execute(&proposal).is_ok();
+    if executed {
+        record_spend(kind);
+    }
+
+    // the reward pool is taken out of supply and the losing side is slashed now,
+    // every voter claims its own pledges and share later
+    let (winning, losing) = if passed {
+        (pool.0, pool.1)
+    } else {
+        (pool.1, pool.0)
+    };
+    // a tally below quorum gets no pool, a handful of token can not take it
+    let total = pool.0 + pool.1;
+    let mut reward_pool = if winning.is_zero() || total < quorum {
+        U256::zero()
+    } else {
+        reward.min(read_key("supply"))
+    };
+    update_key("supply", read_key::<U256>("supply") - reward_pool);
+    let slashed = (losing * read_key::<U256>("slash_rate") / 10000).min(losing);
+    let slash_target: U256 = read_key("slash_target");
+    // burned slashed token stays owned by the losers until they claim
+    let burn = slash_target == U256::from(2);
+    if slash_target.is_zero() && !winning.is_zero() {
+        reward_pool += slashed;
+    } else if !burn {
+        update_key("supply", read_key::<U256>("supply") + slashed);
+    }
+    let id: u64 = read_key::<Option<_>>("proposal_id").unwrap();
+    write_item(
+        "results",
+        &id.to_string(),
+        (
+            (passed, executed, closed_by),
+            (pool, (reward_pool, losing - slashed)),
+            burn,
+        ),
+    );
+    events::proposal_resolved(id, passed, executed, closed_by);
+
+    // the deposit is returned when the proposal reached quorum, and forfeited
+    // when it expired without quorum or was rejected by a large margin
+    let margin: U256 = read_key("deposit_margin");
+    let crushed = !passed && !total.is_zero() && pool.1 * 10000 >= total * margin;
+    settle_deposit(total < quorum || crushed);
+    clear_proposal();
+}
+
+// take back the active proposal
+// the proposer can while nobody else pledged on it, the guardian always can
+#[no_mangle]
+pub extern "C" fn cancel_proposal() {
+    let caller = caller();
+    let pool: (U256, U256) = match read_key::<Option<_>>("pool") {
+        Some(pool) => pool,
+        None => revert(OnlineError::NoSuchProposal),
+    };
+    let id: u64 = read_key::<Option<_>>("proposal_id").unwrap();
+    let (proposer, _) = read_key::<Option<(Key, U256)>>("deposit").unwrap();
+    let by_guardian = read_key::<Option<Key>>("guardian") == Some(caller);
+    if !by_guardian {
+        if caller != proposer {
+            revert(OnlineError::NoPermission)
+        }
+        let (upvote, downvote) = pledge_of(id, caller);
+        if pool.0 + pool.1 > upvote + downvote {
+            revert(OnlineError::AlreadyPledged)
         }
-        pledges_back(pool.0 > pool.1);
-        update_key::<Option<Vec<(AccountHash, U256, bool)>>>("pledges", None);
-        update_key::<Option<String>>("proposal",None);
-        update_key::<Option<U256>>("vote_limit",None);
+    }
+
+    // recorded as rejected without slashing, so every pledge can be claimed back
+    update_key::<Option<(U256, U256)>>("pool", None);
+    write_item(
+        "results",
+        &id.to_string(),
+        (
+            (false, false, CANCELLED),
+            (pool, (U256::zero(), pool.0)),
+            false,
+        ),
+    );
+    events::proposal_resolved(id, false, false, CANCELLED);
+    settle_deposit(by_guardian && caller != proposer);
+    clear_proposal();
+}
+
+// give the deposit of the active proposal back, or forfeit it to `supply`
+fn settle_deposit(forfeited: bool) {
+    let (proposer, deposit) = read_key::<Option<(Key, U256)>>("deposit").unwrap();
+    update_key::<Option<(Key, U256)>>("deposit", None);
+    if deposit.is_zero() {
+        return;
+    }
+    if forfeited {
+        forfeit(proposer, deposit);
+        update_key("supply", read_key::<U256>("supply") + deposit);
     } else {
-        storage::write(runtime::get_key("pool").unwrap().into_uref().unwrap(), pool);
+        unlock(proposer, deposit);
     }
 }
 
+// free the proposal slot
+fn clear_proposal() {
+    update_key::<Option<u64>>("proposal_id", None);
+    update_key::<Option<String>>("proposal", None);
+    update_key::<Option<U256>>("vote_limit", None);
+    update_key::<Option<U256>>("quorum", None);
+    update_key::<Option<(U256, bool)>>("approval", None);
+    update_key::<Option<u64>>("deadline", None);
+    update_key::<Option<u64>>("snapshot", None);
+    update_key::<Option<U256>>("electorate", None);
+}
+
+// owned token of an account at a block time
+#[no_mangle]
+pub extern "C" fn balance_of_at() {
+    // ============
+    // args:
+    // account: Key
+    // time: u64
+    // ============
+    let account: Key = runtime::get_named_arg("account");
+    let time: u64 = runtime::get_named_arg("time");
+    runtime::ret(CLValue::from_t(balance_at(account, time)).unwrap())
+}
+
 // vote
 #[no_mangle]
 pub extern "C" fn new_proposal() {
@@ -148,16 +609,13 @@ pub extern "C" fn new_proposal() {
     // args:
     // proposal: String
     // vote_limit: U256
+    // limit_in_bps: bool (vote_limit is basis points of the circulating supply)
     // vote: bool
     // amount: U256
     // ============
 
-    // Valid there is no active proposal
-    if let Some(_) = read_key::<Option<(U256, U256)>>("pool") {
-        revert(OnlineError::HaveUnFinishProposal)
-    };
     // Valid caller
-    let caller = get_caller();
+    let caller = caller();
     let accounting = get_accounting();
     if !accounting
         .iter()
@@ -169,50 +627,128 @@ pub extern "C" fn new_proposal() {
     let proposal: String = runtime::get_named_arg("proposal");
     let amount: U256 = runtime::get_named_arg("amount");
     let vote: bool = runtime::get_named_arg("vote");
-    let vote_limit: U256 = runtime::get_named_arg("vote_limit");
+    let mut vote_limit: U256 = runtime::get_named_arg("vote_limit");
+    let limit_in_bps: bool = runtime::get_named_arg("limit_in_bps");
 
     let mut s = proposal.split_ascii_whitespace();
     let first = s.next().unwrap();
-    if !(first == "mint" || first == "update") {
+    if !METHODS.contains(&first) {
+        revert(OnlineError::InValidProposal)
+    }
+    if first == "rule" && parse_rule(&proposal).is_none() {
+        revert(OnlineError::InValidProposal)
+    }
+    if first == "vesting" && parse_vesting(&proposal).is_none() {
         revert(OnlineError::InValidProposal)
     }
 
-    if vote_limit < U256::from(20) {
+    // fixed now, later mints or burns do not move it
+    if limit_in_bps {
+        if vote_limit > U256::from(10000) {
+            revert(OnlineError::InValidProposal)
+        }
+        vote_limit = circulating() * vote_limit / 10000;
+    }
+    // vote_limit only closes the vote, it can not be below the quorum
+    // or the smallest share of the circulating supply governance allows
+    let quorum = quorum_of(first);
+    let min_vote_limit = circulating() * read_key::<U256>("min_vote_limit") / 10000;
+    if vote_limit < quorum || vote_limit < min_vote_limit {
         revert(OnlineError::TooSmall)
     }
 
     if amount > vote_limit.div(U256::from(2)) {
         revert(OnlineError::AmountTooBig)
     }
+    // checked again when the draft opens, a draft over the cap could never open
+    if amount * 10000 > vote_limit * read_key::<U256>("max_vote_share") {
+        revert(OnlineError::OverVoteShare)
+    }
+
+    // the deposit and creator's vote are locked while the proposal is a draft,
+    // `finalize_proposal` returns or forfeits the deposit
+    let deposit: U256 = read_key("proposal_deposit");
+    lock(caller, deposit + amount);
+
+    let id: u64 = read_key("next_proposal_id");
+    update_key("next_proposal_id", id + 1);
+    events::proposal_created(id, caller, &proposal);
+    add_draft((
+        id,
+        (caller, proposal, (vote_limit, circulating())),
+        ((vote, amount, deposit), (now(), vec![], U256::zero())),
+    ));
+}
+
+// put a sponsored draft into the proposal slot
+pub fn open_proposal(draft: Draft) {
+    let (
+        id,
+        (proposer, proposal, (vote_limit, electorate)),
+        ((vote, amount, deposit), (created, _, _)),
+    ) = draft;
+    let kind = proposal.split_ascii_whitespace().next().unwrap();
+    // rules may have changed while it was a draft
+    let quorum = quorum_of(kind);
+    let deadline = now() + period_of(kind);
 
+    update_key("deposit", Some((proposer, deposit)));
+    update_key("proposal_id", Some(id));
     update_key("proposal", Some(proposal));
-    update_key("vote_limit", Some(vote_limit));
-    update_key("pool", Some((U256::from(0), U256::from(0))));
-    let pledge: Vec<(AccountHash, U256, bool)> = vec![];
-    update_key("pledges", Some(pledge));
+    update_key("vote_limit", Some(vote_limit.max(quorum)));
+    update_key("quorum", Some(quorum));
+    update_key("approval", Some(approval_of(kind)));
+    update_key("deadline", Some(deadline));
+    // voting power is what accounts owned when the proposal was created,
+    // token moved while it waited for sponsors does not count
+    update_key("snapshot", Some(created));
+    update_key("electorate", Some(electorate));
+    events::proposal_opened(id);
 
     // Save creator's vote
-    if let Some(mut pool) = read_key::<Option<(U256, U256)>>("pool") {
-        if amount > U256::from(0) {
-            pledges(caller, amount, vote);
-            if vote {
-                pool = (pool.0 + amount, pool.1);
-            } else {
-                pool = (pool.0, pool.1 + amount);
-            }
+    let mut pool = (U256::from(0), U256::from(0));
+    if amount > U256::from(0) {
+        record_pledge(proposer, amount, vote);
+        if vote {
+            pool = (pool.0 + amount, pool.1);
+        } else {
+            pool = (pool.0, pool.1 + amount);
         }
-        update_key("pool", Some(pool));
     }
+    update_key("pool", Some(pool));
+}
+
+// the immediate caller, an account or the package of a calling contract
+pub fn caller() -> Key {
+    let call_stack = runtime::get_call_stack();
+    // the last element is this contract
+    match call_stack.iter().rev().nth(1).unwrap() {
+        CallStackElement::Session { account_hash } => Key::Account(*account_hash),
+        CallStackElement::StoredSession { account_hash, .. } => Key::Account(*account_hash),
+        CallStackElement::StoredContract {
+            contract_package_hash,
+            ..
+        } => Key::Hash(contract_package_hash.value()),
+    }
+}
+
+// only accounts and contracts can hold token
+pub fn is_holder(key: &Key) -> bool {
+    matches!(key, Key::Account(_) | Key::Hash(_))
+}
+
+pub fn now() -> u64 {
+    u64::from(runtime::get_blocktime())
 }
 
-fn update_key<T>(name: &str, value: T)
+pub fn update_key<T>(name: &str, value: T)
 where
     T: casper_types::CLTyped + casper_types::bytesrepr::ToBytes,
 {
     storage::write(runtime::get_key(name).unwrap().into_uref().unwrap(), value);
 }
 
-fn read_key<T>(name: &str) -> T
+pub fn read_key<T>(name: &str) -> T
 where
     T: casper_types::CLTyped + casper_types::bytesrepr::FromBytes,
 {
@@ -221,6 +757,31 @@ where
         .unwrap()
 }
 
+pub fn read_item<T>(dictionary: &str, item: &str) -> Option<T>
+where
+    T: casper_types::CLTyped + casper_types::bytesrepr::FromBytes,
+{
+    let uref = runtime::get_key(dictionary).unwrap().into_uref().unwrap();
+    storage::dictionary_get(uref, item).unwrap()
+}
+
+pub fn write_item<T>(dictionary: &str, item: &str, value: T)
+where
+    T: casper_types::CLTyped + casper_types::bytesrepr::ToBytes,
+{
+    let uref = runtime::get_key(dictionary).unwrap().into_uref().unwrap();
+    storage::dictionary_put(uref, item, value);
+}
+
+// dictionary item keys are at most 64 characters, a formatted Key is longer,
+// so longer keys are hashed
+pub fn item_key<T: casper_types::bytesrepr::ToBytes>(value: &T) -> String {
+    runtime::blake2b(value.to_bytes().unwrap())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
 pub fn online_entries() -> EntryPoints {
     let mut entries = EntryPoints::new();
     entries.add_entry_point(EntryPoint::new(
@@ -228,6 +789,7 @@ pub fn online_entries() -> EntryPoints {
         vec![
             Parameter::new("proposal", CLType::String),
             Parameter::new("vote_limit", CLType::U256),
+            Parameter::new("limit_in_bps", CLType::Bool),
             Parameter::new("vote", CLType::Bool),
             Parameter::new("amount", CLType::U256),
         ],
@@ -245,15 +807,61 @@ pub fn online_entries() -> EntryPoints {
         casper_types::EntryPointAccess::Public,
         casper_types::EntryPointType::Contract,
     ));
+    entries.add_entry_point(EntryPoint::new(
+        "claim",
+        vec![Parameter::new("id", CLType::U64)],
+        CLType::Unit,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    ));
+    entries.add_entry_point(EntryPoint::new(
+        "cancel_proposal",
+        vec![],
+        CLType::Unit,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    ));
+    entries.add_entry_point(EntryPoint::new(
+        "finalize_proposal",
+        vec![],
+        CLType::Unit,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    ));
+    entries.add_entry_point(EntryPoint::new(
+        "balance_of_at",
+        vec![
+            Parameter::new("account", CLType::Key),
+            Parameter::new("time", CLType::U64),
+        ],
+        CLType::U256,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    ));
     entries.add_entry_point(EntryPoint::new(
         "transfer",
         vec![
             Parameter::new("amount", CLType::U256),
-            Parameter::new("recipient", CLType::PublicKey),
+            Parameter::new("recipient", CLType::Key),
         ],
         CLType::Unit,
         casper_types::EntryPointAccess::Public,
         casper_types::EntryPointType::Contract,
     ));
+    entries.add_entry_point(EntryPoint::new(
+        "burn",
+        vec![Parameter::new("amount", CLType::U256)],
+        CLType::Unit,
+        casper_types::EntryPointAccess::Public,
+        casper_types::EntryPointType::Contract,
+    ));
+    add_conviction_entries(&mut entries);
+    add_draft_entries(&mut entries);
+    add_membership_entries(&mut entries);
+    add_query_entries(&mut entries);
+    add_vesting_entries(&mut entries);
+    add_sale_entries(&mut entries);
+    add_curve_entries(&mut entries);
+    add_ragequit_entries(&mut entries);
     entries
 }
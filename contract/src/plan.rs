@@ -9,18 +9,39 @@ use casper_contract::contract_api::{
 };
 use casper_types::{account::AccountHash, contracts::NamedKeys, Key, U256};
 
-use crate::{error::PlanError, gardian, online::online_entries};
+use crate::{
+    accounting::add_checkpoint_keys,
+    conviction::add_conviction_keys,
+    curve::add_curve_keys,
+    draft::add_draft_keys,
+    error::PlanError,
+    events::{self, add_event_keys},
+    gardian,
+    membership::add_membership_keys,
+    online::{now, online_entries},
+    query::add_query_keys,
+    ragequit::add_ragequit_keys,
+    rules::add_rule_keys,
+    sale::add_sale_keys,
+    vesting::{add_vesting_keys, Vesting},
+};
 
 #[no_mangle]
 pub extern "C" fn proposal() {
     gardian("plan".into());
     // args
-    // - plan: String (format: "{supply}")
+    // - plan: String (format: "{supply} {cliff} {duration}")
+    //   cliff and duration (milliseconds) of the founder vesting can be left out,
+    //   founder token is free at once then
     judge_original();
     if runtime::has_key("vote") {
         revert(PlanError::AlreadyHaveProposal);
     };
     let plan: String = get_named_arg("plan");
+    if parse_plan(&plan).is_none() {
+        revert(PlanError::InValidPlan);
+    }
+    events::plan_proposed(get_caller(), &plan);
     storage::write(runtime::get_key("plan").unwrap().into_uref().unwrap(), plan);
     let i = get_original_index();
     // vote is a variable only can exist on plan.
@@ -38,6 +59,7 @@ pub extern "C" fn vote() {
         revert(PlanError::NoProposal);
     };
     let judge: bool = runtime::get_named_arg("vote");
+    events::plan_voted(get_caller(), judge);
     let mut vote =
         storage::read::<Vec<i32>>(runtime::get_key("vote").unwrap().into_uref().unwrap())
             .unwrap()
@@ -63,25 +85,104 @@ fn plan_to_online() {
         runtime::get_key("status").unwrap().into_uref().unwrap(),
         "online".to_string(),
     );
+    events::status_changed("online");
     let mut keys = NamedKeys::new();
 
     let plan: String = storage::read(runtime::get_key("plan").unwrap().into_uref().unwrap())
         .unwrap()
         .unwrap();
-    let sup = U256::from(plan.parse::<usize>().unwrap());
+    let (sup, cliff, duration) = parse_plan(&plan).unwrap();
     let sup_u = storage::new_uref(sup / 100 * 70);
     keys.insert("supply".into(), Key::URef(sup_u));
     keys.insert("reward".into(), Key::URef(storage::new_uref(U256::from(1))));
+    keys.insert(
+        "proposal_deposit".into(),
+        Key::URef(storage::new_uref(U256::from(100))),
+    );
+    keys.insert(
+        "deposit_margin".into(),
+        Key::URef(storage::new_uref(U256::from(6667))),
+    );
+    keys.insert(
+        "deposit".into(),
+        Key::URef(storage::new_uref::<Option<(Key, U256)>>(None)),
+    );
+    keys.insert(
+        "guardian".into(),
+        Key::URef(storage::new_uref::<Option<Key>>(None)),
+    );
+    keys.insert(
+        "slash_rate".into(),
+        Key::URef(storage::new_uref(U256::zero())),
+    );
+    keys.insert(
+        "slash_target".into(),
+        Key::URef(storage::new_uref(U256::zero())),
+    );
+    // 1% of the circulating supply
+    keys.insert(
+        "min_vote_limit".into(),
+        Key::URef(storage::new_uref(U256::from(100))),
+    );
+    // no cap by default
+    keys.insert(
+        "max_vote_share".into(),
+        Key::URef(storage::new_uref(U256::from(10000))),
+    );
     let originals: Vec<AccountHash> = {
         let uref = runtime::get_key("originals").unwrap().into_uref().unwrap();
         storage::read::<Vec<AccountHash>>(uref).unwrap().unwrap()
     };
-    let v: Vec<(AccountHash, U256)> = originals.into_iter().map(|f| (f, sup / 10)).collect();
+    let total = sup / 100 * 70 + sup / 10 * originals.len();
+    keys.insert("total_supply".into(), Key::URef(storage::new_uref(total)));
+    keys.insert(
+        "total_burned".into(),
+        Key::URef(storage::new_uref(U256::zero())),
+    );
+    add_checkpoint_keys(
+        &mut keys,
+        originals
+            .iter()
+            .map(|f| (Key::Account(*f), sup / 10))
+            .collect(),
+    );
+    originals
+        .iter()
+        .for_each(|original| events::mint(Key::Account(*original), sup / 10));
+    // founder token is owned at once but locked in a schedule when it vests
+    let vesting = duration > 0;
+    let founders: Vec<Vesting> = originals
+        .iter()
+        .filter(|_| vesting)
+        .enumerate()
+        .map(|(id, f)| {
+            let time = (now(), cliff, duration);
+            (
+                id as u64,
+                (Key::Account(*f), sup / 10, U256::zero()),
+                (time, (false, U256::zero(), U256::zero())),
+            )
+        })
+        .collect();
+    add_vesting_keys(&mut keys, founders);
+    let free = if vesting { U256::zero() } else { sup / 10 };
+    let v: Vec<(Key, U256)> = originals
+        .into_iter()
+        .map(|f| (Key::Account(f), free))
+        .collect();
     storage::write(runtime::get_key("accounting").unwrap().into_uref().unwrap(), v);
     keys.insert("accounting".into(), runtime::get_key("accounting").unwrap());
+    for name in ["pledges", "pledged_proposals", "results"].iter() {
+        let dictionary = storage::new_dictionary(name).unwrap();
+        keys.insert((*name).into(), Key::URef(dictionary));
+    }
+    keys.insert(
+        "next_proposal_id".into(),
+        Key::URef(storage::new_uref(0u64)),
+    );
     keys.insert(
-        "pledges".into(),
-        Key::URef(storage::new_uref::<Option<Vec<(AccountHash, U256, bool)>>>(None)),
+        "proposal_id".into(),
+        Key::URef(storage::new_uref::<Option<u64>>(None)),
     );
     keys.insert(
         "pool".into(),
@@ -95,6 +196,27 @@ fn plan_to_online() {
         "proposal".into(),
         Key::URef(storage::new_uref::<Option<String>>(None)),
     );
+    keys.insert(
+        "deadline".into(),
+        Key::URef(storage::new_uref::<Option<u64>>(None)),
+    );
+    keys.insert(
+        "snapshot".into(),
+        Key::URef(storage::new_uref::<Option<u64>>(None)),
+    );
+    keys.insert(
+        "electorate".into(),
+        Key::URef(storage::new_uref::<Option<U256>>(None)),
+    );
+    add_conviction_keys(&mut keys);
+    add_event_keys(&mut keys);
+    add_draft_keys(&mut keys);
+    add_membership_keys(&mut keys);
+    add_query_keys(&mut keys);
+    add_rule_keys(&mut keys);
+    add_sale_keys(&mut keys);
+    add_curve_keys(&mut keys);
+    add_ragequit_keys(&mut keys);
     let (hash, _) = new_contract(online_entries(), Some(keys), None, None);
     storage::write(
         runtime::get_key("DAO_contract_hash")
@@ -106,6 +228,23 @@ fn plan_to_online() {
     runtime::remove_key("name")
 }
 
+// "{supply}" or "{supply} {cliff} {duration}", the cliff can not be after the end
+fn parse_plan(plan: &str) -> Option<(U256, u64, u64)> {
+    let mut plan = plan.split_ascii_whitespace();
+    let sup = U256::from(plan.next()?.parse::<usize>().ok()?);
+    let (cliff, duration) = match (plan.next(), plan.next()) {
+        (None, _) => (0, 0),
+        (Some(cliff), Some(duration)) => {
+            (cliff.parse::<u64>().ok()?, duration.parse::<u64>().ok()?)
+        }
+        (Some(_), None) => return None,
+    };
+    if plan.next().is_some() || cliff > duration {
+        return None;
+    }
+    Some((sup, cliff, duration))
+}
+
 // judge the caller is one of the originals
 fn judge_original() {
     let caller = runtime::get_caller();
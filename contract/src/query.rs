@@ -0,0 +1,156 @@
+use alloc::{string::String, vec, vec::Vec};
+use casper_contract::contract_api::runtime;
+use casper_types::{
+    account::AccountHash, contracts::NamedKeys, CLType, CLTyped, CLValue, EntryPoint,
+    EntryPointAccess, EntryPointType, EntryPoints, Key, Parameter, U256,
+};
+
+use crate::{
+    accounting::{get_accounting, owned},
+    online::read_key,
+};
+
+// ============================
+// Read-only entry points for other contracts.
+// Every getter only reads the storage and hands the value back with
+// `runtime::ret`, so a contract can call them with `call_contract`.
+
+// owned token of an account, free plus pledged, staked or locked in vesting
+#[no_mangle]
+pub extern "C" fn balance_of() {
+    // ============
+    // args:
+    // account: Key
+    // ============
+    let account: Key = runtime::get_named_arg("account");
+    ret(owned(account))
+}
+
+// free token of an account, what it can transfer or pledge now
+#[no_mangle]
+pub extern "C" fn free_balance_of() {
+    // ============
+    // args:
+    // account: Key
+    // ============
+    let account: Key = runtime::get_named_arg("account");
+    let balance = get_accounting()
+        .into_iter()
+        .find(|(_account, _)| _account == &account)
+        .map(|(_, balance)| balance)
+        .unwrap_or_default();
+    ret(balance)
+}
+
+#[no_mangle]
+pub extern "C" fn total_supply() {
+    ret(read_key::<U256>("total_supply"))
+}
+
+#[no_mangle]
+pub extern "C" fn total_burned() {
+    ret(read_key::<U256>("total_burned"))
+}
+
+// what is left in the reserve
+#[no_mangle]
+pub extern "C" fn remaining_supply() {
+    ret(read_key::<U256>("supply"))
+}
+
+#[no_mangle]
+pub extern "C" fn reward() {
+    ret(read_key::<U256>("reward"))
+}
+
+// (id, proposal) of the proposal in the slot
+#[no_mangle]
+pub extern "C" fn active_proposal() {
+    let id: Option<u64> = read_key("proposal_id");
+    let proposal: Option<String> = read_key("proposal");
+    ret(id.zip(proposal))
+}
+
+// (upvote, downvote) of the proposal in the slot
+#[no_mangle]
+pub extern "C" fn tally() {
+    ret(read_key::<Option<(U256, U256)>>("pool"))
+}
+
+#[no_mangle]
+pub extern "C" fn status() {
+    ret(read_key::<String>("status"))
+}
+
+#[no_mangle]
+pub extern "C" fn originals() {
+    ret(read_key::<Vec<AccountHash>>("originals"))
+}
+
+// members who vote on member proposals, see membership.rs
+#[no_mangle]
+pub extern "C" fn members() {
+    ret(read_key::<Vec<AccountHash>>("members"))
+}
+
+// whether an account is a member, a contract never is
+#[no_mangle]
+pub extern "C" fn is_member() {
+    // ============
+    // args:
+    // account: Key
+    // ============
+    let account: Key = runtime::get_named_arg("account");
+    let members: Vec<AccountHash> = read_key("members");
+    ret(account
+        .into_account()
+        .map_or(false, |account| members.contains(&account)))
+}
+
+fn ret<T: CLTyped + casper_types::bytesrepr::ToBytes>(value: T) {
+    runtime::ret(CLValue::from_t(value).unwrap())
+}
+
+pub fn add_query_keys(keys: &mut NamedKeys) {
+    keys.insert("status".into(), runtime::get_key("status").unwrap());
+}
+
+pub fn add_query_entries(entries: &mut EntryPoints) {
+    for name in ["balance_of", "free_balance_of"].iter() {
+        entries.add_entry_point(EntryPoint::new(
+            *name,
+            vec![Parameter::new("account", CLType::Key)],
+            CLType::U256,
+            EntryPointAccess::Public,
+            EntryPointType::Contract,
+        ));
+    }
+    entries.add_entry_point(EntryPoint::new(
+        "is_member",
+        vec![Parameter::new("account", CLType::Key)],
+        CLType::Bool,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    for (name, ret) in [
+        ("total_supply", CLType::U256),
+        ("total_burned", CLType::U256),
+        ("remaining_supply", CLType::U256),
+        ("reward", CLType::U256),
+        ("active_proposal", Option::<(u64, String)>::cl_type()),
+        ("tally", Option::<(U256, U256)>::cl_type()),
+        ("status", CLType::String),
+        ("originals", Vec::<AccountHash>::cl_type()),
+        ("members", Vec::<AccountHash>::cl_type()),
+    ]
+    .iter()
+    {
+        entries.add_entry_point(EntryPoint::new(
+            *name,
+            vec![],
+            ret.clone(),
+            EntryPointAccess::Public,
+            EntryPointType::Contract,
+        ));
+    }
+}
@@ -0,0 +1,100 @@
+use alloc::vec;
+use casper_contract::contract_api::{
+    runtime::{self, revert},
+    storage, system,
+};
+use casper_types::{
+    contracts::NamedKeys, CLType, EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, Key,
+    Parameter, URef, U256,
+};
+
+use crate::{
+    accounting::reduce,
+    error::OnlineError,
+    events,
+    online::{burned, caller, circulating, now, pledged_proposals, read_key, update_key},
+    sale::{to_u256, to_u512, treasury},
+};
+
+// ============================
+// Ragequit.
+// A member burns token and gets the same share of the CSPR in `treasury`
+// as the token is of the circulating supply. The CSPR backing the bonding
+// curve (`curve_reserve`) is not shared, it pays the sells on the curve.
+//
+// It is not possible while the member has pledges which are not claimed,
+// nor for `ragequit_grace` after a spending proposal passed or a conviction
+// grant was funded.
+//
+// The var in the storage used
+// - ragequit_grace: U256 (milliseconds after a spending proposal passed)
+// - last_spend: u64 (block time of the last spending proposal or conviction grant)
+
+// proposals which pay token out of the reserve
+const SPENDING: [&str; 2] = ["mint", "vesting"];
+
+#[no_mangle]
+pub extern "C" fn ragequit() {
+    // ============
+    // args:
+    // purse: URef (gets the CSPR)
+    // amount: U256
+    // ============
+    let account = caller();
+    let purse: URef = runtime::get_named_arg("purse");
+    let amount: U256 = runtime::get_named_arg("amount");
+    if amount.is_zero() {
+        revert(OnlineError::NoZero)
+    }
+    if !pledged_proposals(account).is_empty() {
+        revert(OnlineError::ActivePledges)
+    }
+    let grace = read_key::<U256>("ragequit_grace").as_u64();
+    if now() < read_key::<u64>("last_spend").saturating_add(grace) {
+        revert(OnlineError::InGracePeriod)
+    }
+
+    reduce(account, amount);
+    let shared = to_u256(system::get_purse_balance(treasury()).unwrap())
+        .saturating_sub(read_key("curve_reserve"));
+    let payout = shared * amount / circulating();
+    burned(amount);
+    events::burn(Some(account), amount);
+    if !payout.is_zero() {
+        system::transfer_from_purse_to_purse(treasury(), purse, to_u512(payout), None).unwrap();
+    }
+}
+
+// called by `resolve` with every executed proposal
+pub fn record_spend(kind: &str) {
+    if SPENDING.contains(&kind) {
+        spent();
+    }
+}
+
+// start the grace period, also called when a conviction grant is funded
+pub fn spent() {
+    update_key("last_spend", now());
+}
+
+pub fn add_ragequit_keys(keys: &mut NamedKeys) {
+    // one day
+    keys.insert(
+        "ragequit_grace".into(),
+        Key::URef(storage::new_uref(U256::from(24 * 60 * 60 * 1000u64))),
+    );
+    keys.insert("last_spend".into(), Key::URef(storage::new_uref(0u64)));
+}
+
+pub fn add_ragequit_entries(entries: &mut EntryPoints) {
+    entries.add_entry_point(EntryPoint::new(
+        "ragequit",
+        vec![
+            Parameter::new("purse", CLType::URef),
+            Parameter::new("amount", CLType::U256),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+}
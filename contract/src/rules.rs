@@ -0,0 +1,151 @@
+use alloc::{string::String, vec, vec::Vec};
+use casper_contract::contract_api::storage;
+use casper_types::{contracts::NamedKeys, Key, U256};
+
+use crate::online::{circulating, read_key, update_key};
+
+// ============================
+// Proposal categories and their resolution rules.
+// - ordinary: routine spending (mint, vesting)
+// - constitutional: parameters, rules and everything else (update, rule)
+//
+// `vote_limit` only says when the vote can be closed early, whether the
+// proposal runs is decided on the final tally with the rule of its category:
+// - quorum: enough token has to be pledged, either absolute or in basis points
+//   of the circulating supply
+// - approval: basis points of the pledged token which have to upvote
+// - tie: whether a tally which hits the approval exactly runs
+// quorum, approval and tie are fixed when the proposal is opened, a rule
+// proposal passing meanwhile does not change how it is judged
+// - period: milliseconds the vote is open
+//
+// Rule proposal format (a rule proposal is itself constitutional):
+// "rule {category} {quorum} {abs|bps} {approval} {pass|reject} {period}"
+//
+// The var in the storage used
+// - proposal_rules: Vec<(String, (U256, bool), (U256, bool, u64))>
+//   (category, (quorum, quorum_in_bps), (approval_bps, tie_passes, period))
+// - quorum: Option<U256> (quorum of the active proposal)
+// - approval: Option<(U256, bool)> (approval_bps and tie_passes of the active proposal)
+
+pub type Rule = (String, (U256, bool), (U256, bool, u64));
+
+const CATEGORIES: [&str; 2] = ["ordinary", "constitutional"];
+
+pub fn category_of(kind: &str) -> &'static str {
+    match kind {
+        "mint" | "vesting" => "ordinary",
+        _ => "constitutional",
+    }
+}
+
+pub fn rule_of(kind: &str) -> Rule {
+    let category = category_of(kind);
+    read_key::<Vec<Rule>>("proposal_rules")
+        .into_iter()
+        .find(|rule| rule.0 == category)
+        .unwrap()
+}
+
+pub fn set_rule(rule: Rule) {
+    let mut rules: Vec<Rule> = read_key("proposal_rules");
+    match rules.iter_mut().find(|_rule| _rule.0 == rule.0) {
+        Some(_rule) => *_rule = rule,
+        None => rules.push(rule),
+    }
+    update_key("proposal_rules", rules);
+}
+
+// absolute quorum for a new proposal of this type
+pub fn quorum_of(kind: &str) -> U256 {
+    let (_, (quorum, in_bps), _) = rule_of(kind);
+    if in_bps {
+        circulating() * quorum / 10000
+    } else {
+        quorum
+    }
+}
+
+// approval and tie for a new proposal of this type
+pub fn approval_of(kind: &str) -> (U256, bool) {
+    let (_, _, (approval, tie_passes, _)) = rule_of(kind);
+    (approval, tie_passes)
+}
+
+pub fn period_of(kind: &str) -> u64 {
+    (rule_of(kind).2).2
+}
+
+// judge the final tally (upvote, downvote) of a proposal with the rule it was opened with
+pub fn approved(pool: (U256, U256), quorum: U256, (approval, tie_passes): (U256, bool)) -> bool {
+    let total = pool.0 + pool.1;
+    if total.is_zero() || total < quorum {
+        return false;
+    }
+    let upvote = pool.0 * 10000;
+    let needed = total * approval;
+    if upvote == needed {
+        tie_passes
+    } else {
+        upvote > needed
+    }
+}
+
+pub fn parse_rule(proposal: &str) -> Option<Rule> {
+    let mut s = proposal.split_ascii_whitespace();
+    if s.next()? != "rule" {
+        return None;
+    }
+    let category = s.next()?;
+    if !CATEGORIES.contains(&category) {
+        return None;
+    }
+    let quorum = U256::from_dec_str(s.next()?).ok()?;
+    let in_bps = match s.next()? {
+        "abs" => false,
+        "bps" => true,
+        _ => return None,
+    };
+    let approval = U256::from_dec_str(s.next()?).ok()?;
+    let tie_passes = match s.next()? {
+        "pass" => true,
+        "reject" => false,
+        _ => return None,
+    };
+    let period = s.next()?.parse::<u64>().ok()?;
+    if approval > U256::from(10000) || (in_bps && quorum > U256::from(10000)) {
+        return None;
+    }
+    Some((
+        category.into(),
+        (quorum, in_bps),
+        (approval, tie_passes, period),
+    ))
+}
+
+pub fn add_rule_keys(keys: &mut NamedKeys) {
+    const DAY: u64 = 24 * 60 * 60 * 1000;
+    let rules: Vec<Rule> = vec![
+        // 20 token, simple majority, a week
+        (
+            String::from("ordinary"),
+            (U256::from(20), false),
+            (U256::from(5000), false, 7 * DAY),
+        ),
+        // 10% of circulating supply, two thirds, two weeks
+        (
+            String::from("constitutional"),
+            (U256::from(1000), true),
+            (U256::from(6667), false, 14 * DAY),
+        ),
+    ];
+    keys.insert("proposal_rules".into(), Key::URef(storage::new_uref(rules)));
+    keys.insert(
+        "quorum".into(),
+        Key::URef(storage::new_uref::<Option<U256>>(None)),
+    );
+    keys.insert(
+        "approval".into(),
+        Key::URef(storage::new_uref::<Option<(U256, bool)>>(None)),
+    );
+}
@@ -0,0 +1,134 @@
+use alloc::{vec, vec::Vec};
+use casper_contract::contract_api::{
+    runtime::{self, revert},
+    storage, system,
+};
+use casper_types::{
+    contracts::NamedKeys, CLType, EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, Key,
+    Parameter, URef, U256, U512,
+};
+
+use crate::{
+    error::OnlineError,
+    online::{caller, mint_to, now, read_key, update_key},
+};
+
+// ============================
+// Token sale.
+// Anyone pays CSPR into the `treasury` purse of the DAO and gets token from
+// `supply` at `sale_price`. Price, caps and the sale window are changed by
+// `update` proposals, a "sale pause" or "sale resume" proposal stops and
+// restarts it.
+//
+// The buyer passes a purse with the CSPR to pay, only what the bought token
+// costs is taken from it.
+//
+// The var in the storage used
+// - treasury: purse (CSPR of the DAO)
+// - sale_price: U256 (motes per token, 0 means no sale)
+// - sale_account_cap: U256 (token one account can buy)
+// - sale_cap: U256 (token the sale can sell)
+// - sale_start: U256, sale_end: U256 (block time of the sale window)
+// - sale_paused: bool
+// - sale_sold: U256
+// - sale_bought: Vec<(Key, U256)> (token bought by each account)
+
+#[no_mangle]
+pub extern "C" fn buy_tokens() {
+    // ============
+    // args:
+    // purse: URef
+    // ============
+    let account = caller();
+    let purse: URef = runtime::get_named_arg("purse");
+    let price: U256 = read_key("sale_price");
+    let now = U256::from(now());
+    if price.is_zero()
+        || read_key::<bool>("sale_paused")
+        || now < read_key::<U256>("sale_start")
+        || now >= read_key::<U256>("sale_end")
+    {
+        revert(OnlineError::SaleClosed)
+    }
+
+    let mut bought: Vec<(Key, U256)> = read_key("sale_bought");
+    let index = match bought.iter().position(|(_account, _)| _account == &account) {
+        Some(index) => index,
+        None => {
+            bought.push((account, U256::zero()));
+            bought.len() - 1
+        }
+    };
+    let sold: U256 = read_key("sale_sold");
+    let paid = to_u256(system::get_purse_balance(purse).unwrap());
+    let amount = (paid / price)
+        .min(read_key::<U256>("sale_account_cap").saturating_sub(bought[index].1))
+        .min(read_key::<U256>("sale_cap").saturating_sub(sold))
+        .min(read_key("supply"));
+    if amount.is_zero() {
+        revert(OnlineError::SaleCapReached)
+    }
+
+    let cost = to_u512(amount * price);
+    system::transfer_from_purse_to_purse(purse, treasury(), cost, None).unwrap();
+    bought[index].1 += amount;
+    update_key("sale_bought", bought);
+    update_key("sale_sold", sold + amount);
+    mint_to(account, amount);
+}
+
+// "sale pause" or "sale resume"
+pub fn execute_sale(order: &str) -> Result<(), OnlineError> {
+    match order.split_ascii_whitespace().nth(1) {
+        Some("pause") => update_key("sale_paused", true),
+        Some("resume") => update_key("sale_paused", false),
+        _ => return Err(OnlineError::InValidProposal),
+    }
+    Ok(())
+}
+
+pub fn treasury() -> URef {
+    runtime::get_key("treasury").unwrap().into_uref().unwrap()
+}
+
+pub fn to_u256(motes: U512) -> U256 {
+    let mut bytes = [0u8; 64];
+    motes.to_little_endian(&mut bytes);
+    U256::from_little_endian(&bytes[..32])
+}
+
+pub fn to_u512(amount: U256) -> U512 {
+    let mut bytes = [0u8; 32];
+    amount.to_little_endian(&mut bytes);
+    U512::from_little_endian(&bytes)
+}
+
+pub fn add_sale_keys(keys: &mut NamedKeys) {
+    keys.insert("treasury".into(), Key::URef(system::create_purse()));
+    // no sale until governance sets a price
+    for name in [
+        "sale_price",
+        "sale_account_cap",
+        "sale_cap",
+        "sale_start",
+        "sale_end",
+        "sale_sold",
+    ]
+    .iter()
+    {
+        keys.insert((*name).into(), Key::URef(storage::new_uref(U256::zero())));
+    }
+    keys.insert("sale_paused".into(), Key::URef(storage::new_uref(false)));
+    let bought: Vec<(Key, U256)> = vec![];
+    keys.insert("sale_bought".into(), Key::URef(storage::new_uref(bought)));
+}
+
+pub fn add_sale_entries(entries: &mut EntryPoints) {
+    entries.add_entry_point(EntryPoint::new(
+        "buy_tokens",
+        vec![Parameter::new("purse", CLType::URef)],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+}
@@ -0,0 +1,315 @@
+use alloc::{vec, vec::Vec};
+use casper_contract::contract_api::{
+    runtime::{self, revert},
+    storage,
+};
+use casper_types::{
+    contracts::NamedKeys, CLType, CLValue, EntryPoint, EntryPointAccess, EntryPointType,
+    EntryPoints, Key, Parameter, U256,
+};
+
+use crate::{
+    accounting::{forfeit, lock, unlock},
+    error::OnlineError,
+    events,
+    online::{caller, is_holder, mint_to, now, read_key, update_key},
+};
+
+// ============================
+// Vesting schedules.
+// Token in a schedule is owned by the beneficiary (it is in `checkpoints`)
+// but locked, like a pledge. `release` unlocks what has vested: nothing before
+// the cliff, then linearly until the end of the duration.
+//
+// Grants are paid by an ordinary proposal which moves token from `supply`
+// into a new schedule:
+// "vesting {beneficiary} {amount} {start} {cliff} {duration} {revocable|irrevocable}"
+// A constitutional proposal "revoke {id}" gives the token of a revocable
+// schedule which has not vested yet back to `supply`.
+//
+// When `vesting_votes` is on, locked token can be pledged too. Such a pledge is
+// recorded in `vesting_pledges` and what comes back from it on `claim` is
+// locked in the schedules again, so voting never frees unvested token.
+//
+// The var in the storage used
+// - vestings: Vec<(u64, (Key, U256, U256), ((u64, u64, u64), (bool, U256, U256)))>
+//   (id, (beneficiary, total, released), ((start, cliff, duration), (revocable, pledged, revoked)))
+//   revoked is unvested token which was pledged when the schedule was revoked,
+//   it goes to `supply` when it comes back from the pledge
+// - vesting_next_id: u64
+// - vesting_pledges: Vec<(u64, Key, U256)> (proposal id, beneficiary, pledged from schedules)
+// - vesting_votes: U256 (0: locked token can not vote, otherwise it can)
+
+pub type Vesting = (
+    u64,
+    (Key, U256, U256),
+    ((u64, u64, u64), (bool, U256, U256)),
+);
+
+#[no_mangle]
+pub extern "C" fn release() {
+    // ============
+    // args:
+    // id: u64
+    // ============
+    let account = caller();
+    let id: u64 = runtime::get_named_arg("id");
+    let mut vestings: Vec<Vesting> = read_key("vestings");
+    let vesting = match vestings.iter_mut().find(|vesting| vesting.0 == id) {
+        Some(vesting) => vesting,
+        None => revert(OnlineError::NoSuchProposal),
+    };
+    if (vesting.1).0 != account {
+        revert(OnlineError::NoPermission)
+    }
+    // token pledged out of the schedule is released once it comes back
+    let amount = releasable(vesting).min(present(vesting));
+    if amount.is_zero() {
+        revert(OnlineError::NothingToClaim)
+    }
+    (vesting.1).2 += amount;
+    unlock(account, amount);
+    update_key("vestings", vestings);
+    events::released(id, account, amount);
+}
+
+// vested but not released token of an account
+#[no_mangle]
+pub extern "C" fn vested_of() {
+    // ============
+    // args:
+    // account: Key
+    // ============
+    let account: Key = runtime::get_named_arg("account");
+    let amount = schedules_of(account)
+        .iter()
+        .fold(U256::zero(), |sum, vesting| sum + releasable(vesting));
+    runtime::ret(CLValue::from_t(amount).unwrap())
+}
+
+// not yet vested token of an account
+#[no_mangle]
+pub extern "C" fn locked_of() {
+    // ============
+    // args:
+    // account: Key
+    // ============
+    let account: Key = runtime::get_named_arg("account");
+    let amount = schedules_of(account)
+        .iter()
+        .fold(U256::zero(), |sum, vesting| {
+            sum + (vesting.1).1 - vested(vesting)
+        });
+    runtime::ret(CLValue::from_t(amount).unwrap())
+}
+
+// called by `pledges` for what is missing from the free token,
+// takes locked token out of the schedules of the account
+pub fn pledge_locked(proposal: u64, account: Key, amount: U256) {
+    if read_key::<U256>("vesting_votes").is_zero() {
+        revert(OnlineError::UserHaveNoEnoughToken)
+    }
+    let mut vestings: Vec<Vesting> = read_key("vestings");
+    let mut left = amount;
+    for vesting in vestings
+        .iter_mut()
+        .filter(|vesting| (vesting.1).0 == account)
+    {
+        let taken = left.min(present(vesting));
+        ((vesting.2).1).1 += taken;
+        left -= taken;
+    }
+    if !left.is_zero() {
+        revert(OnlineError::UserHaveNoEnoughToken)
+    }
+    update_key("vestings", vestings);
+
+    let mut pledges: Vec<(u64, Key, U256)> = read_key("vesting_pledges");
+    match pledges
+        .iter_mut()
+        .find(|(id, acc, _)| id == &proposal && acc == &account)
+    {
+        Some((_, _, pledged)) => *pledged += amount,
+        None => pledges.push((proposal, account, amount)),
+    }
+    update_key("vesting_pledges", pledges);
+}
+
+// called by `claim` with the pledged token given back to the account,
+// the part which came out of the schedules goes back in, slashed token is lost
+pub fn return_pledged(proposal: u64, account: Key, returned: U256) {
+    let mut pledges: Vec<(u64, Key, U256)> = read_key("vesting_pledges");
+    let index = match pledges
+        .iter()
+        .position(|(id, acc, _)| id == &proposal && acc == &account)
+    {
+        Some(index) => index,
+        None => return,
+    };
+    let (_, _, pledged) = pledges.remove(index);
+    update_key("vesting_pledges", pledges);
+
+    let mut back = pledged.min(returned);
+    lock(account, back);
+    let mut lost = pledged - back;
+    let mut clawed = U256::zero();
+    let mut vestings: Vec<Vesting> = read_key("vestings");
+    for vesting in vestings
+        .iter_mut()
+        .filter(|vesting| (vesting.1).0 == account)
+    {
+        let (_, taken, revoked) = (vesting.2).1;
+        let kept = taken.min(back);
+        back -= kept;
+        let dropped = (taken - kept).min(lost);
+        lost -= dropped;
+        // what a revoke could not take while it was pledged is taken now
+        let claw = kept.min(revoked);
+        clawed += claw;
+        ((vesting.2).1).1 -= kept + dropped;
+        ((vesting.2).1).2 = (revoked - claw).saturating_sub(dropped);
+        (vesting.1).1 -= dropped + claw;
+    }
+    update_key("vestings", vestings);
+    if !clawed.is_zero() {
+        forfeit(account, clawed);
+        update_key("supply", read_key::<U256>("supply") + clawed);
+    }
+}
+
+// pay a grant out of `supply` into a new schedule
+pub fn create_vesting(
+    beneficiary: Key,
+    amount: U256,
+    time: (u64, u64, u64),
+    revocable: bool,
+) -> Result<(), OnlineError> {
+    if amount.is_zero() {
+        return Err(OnlineError::NoZero);
+    }
+    if amount > read_key::<U256>("supply") {
+        return Err(OnlineError::SupplyTooSmall);
+    }
+    mint_to(beneficiary, amount);
+    lock(beneficiary, amount);
+    let id: u64 = read_key("vesting_next_id");
+    update_key("vesting_next_id", id + 1);
+    let mut vestings: Vec<Vesting> = read_key("vestings");
+    vestings.push((
+        id,
+        (beneficiary, amount, U256::zero()),
+        (time, (revocable, U256::zero(), U256::zero())),
+    ));
+    update_key("vestings", vestings);
+    Ok(())
+}
+
+// give the locked token which has not vested back to `supply`,
+// everything left in the schedule is vested from now on
+pub fn revoke(id: u64) -> Result<(), OnlineError> {
+    let mut vestings: Vec<Vesting> = read_key("vestings");
+    let vesting = vestings
+        .iter_mut()
+        .find(|vesting| vesting.0 == id && ((vesting.2).1).0)
+        .ok_or(OnlineError::InValidProposal)?;
+    // vested token is taken from what is present first, the rest of the
+    // unvested token is pledged and taken when it comes back
+    let unvested = present(vesting).saturating_sub(releasable(vesting));
+    let total = (vesting.1).1;
+    let pledged = ((vesting.2).1).1;
+    let unvested_pledged = total
+        .saturating_sub(vested(vesting))
+        .saturating_sub(unvested);
+    let beneficiary = (vesting.1).0;
+    forfeit(beneficiary, unvested);
+    (vesting.1).1 -= unvested;
+    let start = ((vesting.2).0).0;
+    vesting.2 = ((start, 0, 0), (false, pledged, unvested_pledged));
+    update_key("vestings", vestings);
+    update_key("supply", read_key::<U256>("supply") + unvested);
+    Ok(())
+}
+
+// "vesting {beneficiary} {amount} {start} {cliff} {duration} {revocable|irrevocable}"
+pub fn parse_vesting(proposal: &str) -> Option<(Key, U256, (u64, u64, u64), bool)> {
+    let mut s = proposal.split_ascii_whitespace();
+    if s.next()? != "vesting" {
+        return None;
+    }
+    let beneficiary = Key::from_formatted_str(s.next()?).ok().filter(is_holder)?;
+    let amount = U256::from_dec_str(s.next()?).ok()?;
+    let start = s.next()?.parse::<u64>().ok()?;
+    let cliff = s.next()?.parse::<u64>().ok()?;
+    let duration = s.next()?.parse::<u64>().ok()?;
+    let revocable = match s.next()? {
+        "revocable" => true,
+        "irrevocable" => false,
+        _ => return None,
+    };
+    Some((beneficiary, amount, (start, cliff, duration), revocable))
+}
+
+fn schedules_of(account: Key) -> Vec<Vesting> {
+    read_key::<Vec<Vesting>>("vestings")
+        .into_iter()
+        .filter(|vesting| (vesting.1).0 == account)
+        .collect()
+}
+
+fn vested(vesting: &Vesting) -> U256 {
+    let (_, (_, total, _), ((start, cliff, duration), _)) = *vesting;
+    let now = now();
+    if now < start.saturating_add(cliff) {
+        U256::zero()
+    } else if duration == 0 || now >= start.saturating_add(duration) {
+        total
+    } else {
+        total * (now - start) / duration
+    }
+}
+
+fn releasable(vesting: &Vesting) -> U256 {
+    vested(vesting).saturating_sub((vesting.1).2)
+}
+
+// token still locked in the schedule, not released nor pledged
+fn present(vesting: &Vesting) -> U256 {
+    let (_, (_, total, released), (_, (_, pledged, _))) = *vesting;
+    total - released - pledged
+}
+
+pub fn add_vesting_keys(keys: &mut NamedKeys, vestings: Vec<Vesting>) {
+    keys.insert(
+        "vesting_next_id".into(),
+        Key::URef(storage::new_uref(vestings.len() as u64)),
+    );
+    keys.insert("vestings".into(), Key::URef(storage::new_uref(vestings)));
+    let pledges: Vec<(u64, Key, U256)> = vec![];
+    keys.insert(
+        "vesting_pledges".into(),
+        Key::URef(storage::new_uref(pledges)),
+    );
+    keys.insert(
+        "vesting_votes".into(),
+        Key::URef(storage::new_uref(U256::from(1))),
+    );
+}
+
+pub fn add_vesting_entries(entries: &mut EntryPoints) {
+    entries.add_entry_point(EntryPoint::new(
+        "release",
+        vec![Parameter::new("id", CLType::U64)],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    for name in ["vested_of", "locked_of"].iter() {
+        entries.add_entry_point(EntryPoint::new(
+            *name,
+            vec![Parameter::new("account", CLType::Key)],
+            CLType::U256,
+            EntryPointAccess::Public,
+            EntryPointType::Contract,
+        ));
+    }
+}
@@ -1,22 +1,1444 @@
 #[cfg(test)]
 mod tests {
 
-    use casper_engine_test_support::{Code, SessionBuilder, TestContextBuilder};
+    use casper_engine_test_support::{Code, SessionBuilder, TestContext, TestContextBuilder};
     use casper_types::{
-        account::AccountHash, runtime_args, AsymmetricType, ContractHash, PublicKey, RuntimeArgs,
+        account::AccountHash,
+        bytesrepr::{Bytes, FromBytes},
+        runtime_args, AsymmetricType, CLTyped, ContractHash, Key, PublicKey, RuntimeArgs, URef,
         U256, U512,
     };
 
     const ACCOUNT_A: [u8; 32] = [3u8; 32];
     const ACCOUNT_B: [u8; 32] = [6u8; 32];
     const ACCOUNT_C: [u8; 32] = [9u8; 32];
+    // not an original, it is admitted as a member in `membership`
+    const ACCOUNT_D: [u8; 32] = [12u8; 32];
+
+    const HOUR: u64 = 60 * 60 * 1000;
+    const DAY: u64 = 24 * HOUR;
+
+    // (id, (beneficiary, total, released), ((start, cliff, duration), (revocable, pledged, revoked)))
+    type Vesting = (
+        u64,
+        (Key, U256, U256),
+        ((u64, u64, u64), (bool, U256, U256)),
+    );
+
+    // ((passed, executed, closed by), ((unclaimed upvote, unclaimed downvote), (reward left, refund left)), burn)
+    type Outcome = ((bool, bool, u8), ((U256, U256), (U256, U256)), bool);
 
     #[test]
     fn test() {
+        let (mut context, [account_a, account_b, account_c], hash) = create();
+
+        // proposal for plan
+        let proposal_code = Code::Hash(hash.value(), "proposal".into());
+        let proposal = SessionBuilder::new(
+            proposal_code,
+            runtime_args! {"plan" => String::from("100000000")},
+        )
+        .with_address(account_a)
+        .with_authorization_keys(&[account_a])
+        .build();
+        context.run(proposal);
+
+        // vote for plan
+        // when upvote hit to two people, plan goes online
+        let vote_code = Code::Hash(hash.value(), "vote".into());
+        let vote = SessionBuilder::new(vote_code, runtime_args! {"vote" => true})
+            .with_address(account_b)
+            .with_authorization_keys(&[account_b])
+            .build();
+        context.run(vote);
+
+        let status: String = context
+            .query(account_a, &["status".into()])
+            .unwrap()
+            .into_t()
+            .unwrap();
+        assert_eq!(status, "online".to_string());
+
+        // now it's online
+        // And we can get online contract hash for further call
+        let new_hash: ContractHash = context
+            .query(account_a, &["DAO_contract_hash".into()])
+            .unwrap()
+            .into_t()
+            .unwrap();
+
+        println!("Now DAO is online");
+
+        // this will show every originals account have `10000000` token
+        context
+            .query(account_a, &["accounting".into()])
+            .unwrap()
+            .into_t::<Vec<(Key, U256)>>()
+            .unwrap().into_iter().enumerate().map(|(index,(_,f))|{
+                println!("User {}: {}",&index,f)
+            }).count();
+
+        // now create a proposal that will udpate the reward pool to 3000001, and user a will upvote this vote with pledge 1 token
+        // updating is constitutional, so vote_limit has to reach the quorum of 10% circulating supply
+        let proposal_code = Code::Hash(new_hash.value(), "new_proposal".into());
+        let proposal = SessionBuilder::new(
+            proposal_code,
+            runtime_args! {   "proposal"=> String::from("update reward 3000001"),
+            "vote_limit"=> U256::from(3_000_000),
+            "limit_in_bps" => false,
+            "vote" => true,
+            "amount"=> U256::from(1)},
+        )
+        .with_address(account_a)
+        .with_authorization_keys(&[account_a])
+        .build();
+        context.run(proposal);
+
+        println!("Now first proposal is create by uesr a, and user a vote upvote with amount 1. this proposal will set reward pool to 3000001");
+
+        // after create proposal, this will show the account_a only has `9999899` free token now, its pledge of 1 and the deposit of 100 are locked
+        context
+            .query(account_a, &["accounting".into()])
+            .unwrap()
+            .into_t::<Vec<(Key, U256)>>()
+            .unwrap().into_iter().enumerate().map(|(index,(_,f))|{
+                println!("User {}: {}",&index,f)
+            }).count();
+
+        // the proposal is a draft until another member sponsors it
+        let sponsor_code = Code::Hash(new_hash.value(), "sponsor_proposal".into());
+        let sponsor = SessionBuilder::new(sponsor_code, runtime_args! {"id" => 0u64})
+            .with_address(account_c)
+            .with_authorization_keys(&[account_c])
+            .build();
+        context.run(sponsor);
+
+        // other user pledge token for vote
+        // user b pledge 3000000 token this will hit limit to make contract decide execute the proposal or not.
+        // and after execute proposal. user can claim back their money with reward
+        let vote_code = Code::Hash(new_hash.value(), "vote_by_pledges".into());
+        let vote = SessionBuilder::new(
+            vote_code,
+            runtime_args! {
+            "vote" => true,
+            "amount"=> U256::from(3_000_000)},
+        )
+        .with_address(account_b)
+        .with_authorization_keys(&[account_b])
+        .build();
+        context.run(vote);
+
+        // the vote hit the limit, anyone can finalize the proposal now
+        let finalize_code = Code::Hash(new_hash.value(), "finalize_proposal".into());
+        let finalize = SessionBuilder::new(finalize_code, runtime_args! {})
+            .with_address(account_c)
+            .with_authorization_keys(&[account_c])
+            .build();
+        context.run(finalize);
+
+        println!("Now another user voted, and proposal hit the limit of vote, it is finalized and executed. And users can claim their money back with reward");
+
+        // every voter claims its own pledge of the first proposal (id 0)
+        for account in [account_a, account_b].iter() {
+            let claim_code = Code::Hash(new_hash.value(), "claim".into());
+            let claim = SessionBuilder::new(claim_code, runtime_args! {"id" => 0u64})
+                .with_address(*account)
+                .with_authorization_keys(&[*account])
+                .build();
+            context.run(claim);
+        }
+
+        // the pool of this proposal is the reward from before it ran (1),
+        // the winners share it by their pledge (1 : 3000000).
+        // So now user_a have 10000000,
+        // user _b have 10000001
+        context
+            .query(account_a, &["accounting".into()])
+            .unwrap()
+            .into_t::<Vec<(Key, U256)>>()
+            .unwrap().into_iter().enumerate().map(|(index,(_,f))|{
+                println!("User {}: {}",&index,f)
+            }).count();
+
+        // a contract can hold token and vote with it, install the helper contract
+        let voter_code = Code::from("voter.wasm");
+        let voter_session = SessionBuilder::new(
+            voter_code,
+            runtime_args! {"dao" => new_hash, "motes" => U512::from(1_000_000_000_000u64)},
+        )
+        .with_address(account_a)
+        .with_authorization_keys(&[account_a])
+        .build();
+        context.run(voter_session);
+        let voter_hash: ContractHash = context
+            .query(account_a, &["voter_hash".into()])
+            .unwrap()
+            .into_t()
+            .unwrap();
+        let voter: Key = context
+            .query(account_a, &["voter_holder".into()])
+            .unwrap()
+            .into_t()
+            .unwrap();
+
+        // user a sends 10000 token to the contract
+        let transfer_code = Code::Hash(new_hash.value(), "transfer".into());
+        let transfer = SessionBuilder::new(
+            transfer_code,
+            runtime_args! {"amount" => U256::from(10000), "recipient" => voter},
+        )
+        .with_address(account_a)
+        .with_authorization_keys(&[account_a])
+        .build();
+        context.run(transfer);
+
+        // whoever calls the contract, the contract proposes with its own token:
+        // deposit 100 and a pledge of 1000
+        let proposal_code = Code::Hash(voter_hash.value(), "new_proposal".into());
+        let proposal = SessionBuilder::new(
+            proposal_code,
+            runtime_args! {
+            "proposal" => format!("mint 1 {}", account_c.to_formatted_string()),
+            "vote_limit" => U256::from(100),
+            "limit_in_bps" => true,
+            "vote" => true,
+            "amount" => U256::from(1000)},
+        )
+        .with_address(account_b)
+        .with_authorization_keys(&[account_b])
+        .build();
+        context.run(proposal);
+
+        let sponsor_code = Code::Hash(new_hash.value(), "sponsor_proposal".into());
+        let sponsor = SessionBuilder::new(sponsor_code, runtime_args! {"id" => 1u64})
+            .with_address(account_c)
+            .with_authorization_keys(&[account_c])
+            .build();
+        context.run(sponsor);
+
+        // and pledges 500 more
+        let vote_code = Code::Hash(voter_hash.value(), "vote_by_pledges".into());
+        let vote = SessionBuilder::new(
+            vote_code,
+            runtime_args! {"vote" => true, "amount" => U256::from(500)},
+        )
+        .with_address(account_b)
+        .with_authorization_keys(&[account_b])
+        .build();
+        context.run(vote);
+
+        let voter_balance = context
+            .query(account_a, &["accounting".into()])
+            .unwrap()
+            .into_t::<Vec<(Key, U256)>>()
+            .unwrap()
+            .into_iter()
+            .find(|(key, _)| key == &voter)
+            .map(|(_, balance)| balance);
+        assert_eq!(voter_balance, Some(U256::from(8400)));
+        println!("The contract voted with its own token, it has 8400 free token left");
+
+        // conviction voting: user a asks 1000 token for user c,
+        // users a and b stake 16000000 token on it
+        let key_a = Key::Account(account_a);
+        let key_b = Key::Account(account_b);
+        let key_c = Key::Account(account_c);
+        let new_conviction = session(
+            account_a,
+            new_hash,
+            "new_conviction_proposal",
+            runtime_args! {"beneficiary" => key_c, "amount" => U256::from(1000)},
+            HOUR,
+        );
+        context.run(new_conviction.build());
+        for (account, amount) in [(account_a, 9_000_000u64), (account_b, 7_000_000)].iter() {
+            let stake = session(
+                *account,
+                new_hash,
+                "stake_conviction",
+                runtime_args! {"id" => 0u64, "amount" => U256::from(*amount)},
+                HOUR,
+            );
+            context.run(stake.build());
+        }
+        assert_eq!(free(&context, account_a, key_a), U256::from(990_000));
+
+        // the threshold of a small grant is
+        // weight * circulating / ((1 - decay) * max_ratio ^ 2) = 2 * 30000001 * 1000000 / (100 * 200 * 200),
+        // 15000000, so one period (an hour) of 16000000 staked is enough
+        let update = session(
+            account_c,
+            new_hash,
+            "update_conviction",
+            runtime_args! {"id" => 0u64},
+            2 * HOUR,
+        );
+        context.run(update.build());
+        let proposals: Vec<(u64, (Key, U256), (U256, U256, u64))> =
+            read(&context, account_a, "conviction_proposals");
+        assert!(proposals.is_empty());
+        assert_eq!(free(&context, account_a, key_c), U256::from(10_001_000));
+        assert_eq!(
+            read::<U256>(&context, account_a, "supply"),
+            U256::from(69_998_999)
+        );
+        assert_eq!(read::<u64>(&context, account_a, "last_spend"), 2 * HOUR);
+
+        // the stakes are withdrawn from the funded proposal
+        for (account, amount) in [(account_a, 9_000_000u64), (account_b, 7_000_000)].iter() {
+            let withdraw = session(
+                *account,
+                new_hash,
+                "withdraw_conviction",
+                runtime_args! {"id" => 0u64, "amount" => U256::from(*amount)},
+                2 * HOUR,
+            );
+            context.run(withdraw.build());
+        }
+        assert_eq!(free(&context, account_a, key_a), U256::from(9_990_000));
+        assert_eq!(free(&context, account_a, key_b), U256::from(10_000_001));
+        println!("The grant for user c was funded by conviction");
+
+        // the proposal of the contract passes at its deadline, user c gets the token,
+        // the contract its pledge and deposit back with the whole reward pool of 3000001
+        let finalize = session(
+            account_c,
+            new_hash,
+            "finalize_proposal",
+            runtime_args! {},
+            8 * DAY,
+        );
+        context.run(finalize.build());
+        let claim = session(
+            account_b,
+            voter_hash,
+            "claim",
+            runtime_args! {"id" => 1u64},
+            8 * DAY,
+        );
+        context.run(claim.build());
+        assert_eq!(free(&context, account_a, voter), U256::from(3_010_001));
+        assert_eq!(free(&context, account_a, key_c), U256::from(10_001_001));
+
+        // slash half of every losing pledge, to the winners
+        let accounts = [account_a, account_b, account_c];
+        pass(
+            &mut context,
+            accounts,
+            new_hash,
+            "update slash_rate 5000".into(),
+            8 * DAY,
+        );
+        assert_eq!(
+            read::<U256>(&context, account_a, "slash_rate"),
+            U256::from(5000)
+        );
+
+        // users a and c upvote with 2000000 and 1000000, user b loses with 1000000
+        let before: Vec<U256> = [key_a, key_b, key_c]
+            .iter()
+            .map(|key| free(&context, account_a, *key))
+            .collect();
+        let proposal = session(
+            account_a,
+            new_hash,
+            "new_proposal",
+            runtime_args! {
+            "proposal" => String::from("update reward 1000"),
+            "vote_limit" => U256::from(4_000_000),
+            "limit_in_bps" => false,
+            "vote" => true,
+            "amount" => U256::from(2_000_000)},
+            9 * DAY,
+        );
+        context.run(proposal.build());
+        let sponsor = session(
+            account_c,
+            new_hash,
+            "sponsor_proposal",
+            runtime_args! {"id" => 3u64},
+            9 * DAY,
+        );
+        context.run(sponsor.build());
+        for (account, vote) in [(account_b, false), (account_c, true)].iter() {
+            let pledge = session(
+                *account,
+                new_hash,
+                "vote_by_pledges",
+                runtime_args! {"vote" => *vote, "amount" => U256::from(1_000_000)},
+                9 * DAY,
+            );
+            context.run(pledge.build());
+        }
+        let finalize = session(
+            account_c,
+            new_hash,
+            "finalize_proposal",
+            runtime_args! {},
+            9 * DAY,
+        );
+        context.run(finalize.build());
+        for account in accounts.iter() {
+            let claim = session(
+                *account,
+                new_hash,
+                "claim",
+                runtime_args! {"id" => 3u64},
+                9 * DAY,
+            );
+            context.run(claim.build());
+        }
+        // the pool is the old reward 3000001 plus the slashed 500000, shared 2 : 1
+        assert_eq!(
+            free(&context, account_a, key_a),
+            before[0] + U256::from(2_333_334)
+        );
+        assert_eq!(
+            free(&context, account_a, key_b),
+            before[1] - U256::from(500_000)
+        );
+        assert_eq!(
+            free(&context, account_a, key_c),
+            before[2] + U256::from(1_166_667)
+        );
+        assert_eq!(
+            read::<U256>(&context, account_a, "reward"),
+            U256::from(1000)
+        );
+        println!("User b lost half of its pledge to users a and c");
+
+        // a draft nobody sponsors expires after the sponsor window (three days),
+        // user a gets the pledge back and the deposit of 100 goes to supply
+        let before = (
+            free(&context, account_a, key_a),
+            read::<U256>(&context, account_a, "supply"),
+        );
+        let proposal = session(
+            account_a,
+            new_hash,
+            "new_proposal",
+            runtime_args! {
+            "proposal" => String::from("update reward 10"),
+            "vote_limit" => U256::from(1000),
+            "limit_in_bps" => true,
+            "vote" => true,
+            "amount" => U256::from(5000)},
+            10 * DAY,
+        );
+        context.run(proposal.build());
+        assert_eq!(
+            free(&context, account_a, key_a),
+            before.0 - U256::from(5100)
+        );
+        let expire = session(
+            account_b,
+            new_hash,
+            "expire_draft",
+            runtime_args! {"id" => 4u64},
+            13 * DAY,
+        );
+        context.run(expire.build());
+        assert_eq!(free(&context, account_a, key_a), before.0 - U256::from(100));
+        assert_eq!(
+            read::<U256>(&context, account_a, "supply"),
+            before.1 + U256::from(100)
+        );
+
+        // the deposit is forfeited too when two thirds of the pledges vote against,
+        // user b downvotes the rest of vote_limit
+        let before = (
+            free(&context, account_a, key_a),
+            free(&context, account_a, key_b),
+            read::<U256>(&context, account_a, "supply"),
+        );
+        let proposal = session(
+            account_a,
+            new_hash,
+            "new_proposal",
+            runtime_args! {
+            "proposal" => String::from("update reward 10"),
+            "vote_limit" => U256::from(1000),
+            "limit_in_bps" => true,
+            "vote" => true,
+            "amount" => U256::from(1000)},
+            13 * DAY,
+        );
+        context.run(proposal.build());
+        let sponsor = session(
+            account_c,
+            new_hash,
+            "sponsor_proposal",
+            runtime_args! {"id" => 5u64},
+            13 * DAY,
+        );
+        context.run(sponsor.build());
+        let vote_limit = read::<Option<U256>>(&context, account_a, "vote_limit").unwrap();
+        let pledge = session(
+            account_b,
+            new_hash,
+            "vote_by_pledges",
+            runtime_args! {"vote" => false, "amount" => vote_limit - U256::from(1000)},
+            13 * DAY,
+        );
+        context.run(pledge.build());
+        let finalize = session(
+            account_c,
+            new_hash,
+            "finalize_proposal",
+            runtime_args! {},
+            13 * DAY,
+        );
+        context.run(finalize.build());
+        for account in [account_a, account_b].iter() {
+            let claim = session(
+                *account,
+                new_hash,
+                "claim",
+                runtime_args! {"id" => 5u64},
+                13 * DAY,
+            );
+            context.run(claim.build());
+        }
+        // user a loses the deposit and half of its pledge,
+        // user b wins the reward of 1000 and the slashed 500
+        assert_eq!(free(&context, account_a, key_a), before.0 - U256::from(600));
+        assert_eq!(
+            free(&context, account_a, key_b),
+            before.1 + U256::from(1500)
+        );
+        assert_eq!(
+            read::<U256>(&context, account_a, "supply"),
+            before.2 - U256::from(900)
+        );
+        println!("Deposits of an expired draft and a crushed proposal went to supply");
+
+        // only the proposer cancels while nobody else pledged,
+        // it gets the deposit back at once and the pledge with claim
+        let before = free(&context, account_a, key_a);
+        let proposal = session(
+            account_a,
+            new_hash,
+            "new_proposal",
+            runtime_args! {
+            "proposal" => String::from("update reward 10"),
+            "vote_limit" => U256::from(1000),
+            "limit_in_bps" => true,
+            "vote" => true,
+            "amount" => U256::from(5000)},
+            14 * DAY,
+        );
+        context.run(proposal.build());
+        let sponsor = session(
+            account_c,
+            new_hash,
+            "sponsor_proposal",
+            runtime_args! {"id" => 6u64},
+            14 * DAY,
+        );
+        context.run(sponsor.build());
+        let cancel = session(
+            account_b,
+            new_hash,
+            "cancel_proposal",
+            runtime_args! {},
+            14 * DAY,
+        );
+        context.run(cancel.without_expect_success().build());
+        assert_eq!(
+            read::<Option<u64>>(&context, account_a, "proposal_id"),
+            Some(6)
+        );
+        let cancel = session(
+            account_a,
+            new_hash,
+            "cancel_proposal",
+            runtime_args! {},
+            14 * DAY,
+        );
+        context.run(cancel.build());
+        assert_eq!(
+            read::<Option<u64>>(&context, account_a, "proposal_id"),
+            None
+        );
+        assert_eq!(free(&context, account_a, key_a), before - U256::from(5000));
+        let claim = session(
+            account_a,
+            new_hash,
+            "claim",
+            runtime_args! {"id" => 6u64},
+            14 * DAY,
+        );
+        context.run(claim.build());
+        assert_eq!(free(&context, account_a, key_a), before);
+        println!("User a cancelled its proposal and got everything back");
+
+        // vote_limit is the whole circulating supply (39001805), but once more than half
+        // of it upvoted a mint the rest can not reject it, so that vote resolves it
+        let before = free(&context, account_a, key_c);
+        let proposal = session(
+            account_a,
+            new_hash,
+            "new_proposal",
+            runtime_args! {
+            "proposal" => format!("mint 10 {}", account_c.to_formatted_string()),
+            "vote_limit" => U256::from(10000),
+            "limit_in_bps" => true,
+            "vote" => true,
+            "amount" => U256::from(9_000_000)},
+            15 * DAY,
+        );
+        context.run(proposal.build());
+        let sponsor = session(
+            account_c,
+            new_hash,
+            "sponsor_proposal",
+            runtime_args! {"id" => 7u64},
+            15 * DAY,
+        );
+        context.run(sponsor.build());
+        let pledge = session(
+            account_b,
+            new_hash,
+            "vote_by_pledges",
+            runtime_args! {"vote" => true, "amount" => U256::from(9_000_000)},
+            15 * DAY,
+        );
+        context.run(pledge.build());
+        assert_eq!(
+            read::<Option<(U256, U256)>>(&context, account_a, "pool"),
+            Some((U256::from(18_000_000), U256::zero()))
+        );
+        let pledge = session(
+            account_c,
+            new_hash,
+            "vote_by_pledges",
+            runtime_args! {"vote" => true, "amount" => U256::from(2_000_000)},
+            15 * DAY,
+        );
+        context.run(pledge.build());
+        assert_eq!(
+            read::<Option<(U256, U256)>>(&context, account_a, "pool"),
+            None
+        );
+        assert_eq!(
+            free(&context, account_a, key_c),
+            before - U256::from(2_000_000) + U256::from(10)
+        );
+        for account in accounts.iter() {
+            let claim = session(
+                *account,
+                new_hash,
+                "claim",
+                runtime_args! {"id" => 7u64},
+                15 * DAY,
+            );
+            context.run(claim.build());
+        }
+        println!("The mint was resolved early without finalize_proposal");
+
+        // one account can pledge at most 60% of vote_limit
+        pass(
+            &mut context,
+            accounts,
+            new_hash,
+            "update max_vote_share 6000".into(),
+            16 * DAY,
+        );
+        let proposal = session(
+            account_a,
+            new_hash,
+            "new_proposal",
+            runtime_args! {
+            "proposal" => String::from("update reward 10"),
+            "vote_limit" => U256::from(1000),
+            "limit_in_bps" => true,
+            "vote" => true,
+            "amount" => U256::from(1000)},
+            16 * DAY,
+        );
+        context.run(proposal.build());
+        let sponsor = session(
+            account_c,
+            new_hash,
+            "sponsor_proposal",
+            runtime_args! {"id" => 9u64},
+            16 * DAY,
+        );
+        context.run(sponsor.build());
+        let vote_limit = read::<Option<U256>>(&context, account_a, "vote_limit").unwrap();
+        let before = free(&context, account_a, key_b);
+        let pledge = session(
+            account_b,
+            new_hash,
+            "vote_by_pledges",
+            runtime_args! {"vote" => true, "amount" => vote_limit * 6000 / 10000 + 1},
+            16 * DAY,
+        );
+        context.run(pledge.without_expect_success().build());
+        assert_eq!(
+            read::<Option<(U256, U256)>>(&context, account_a, "pool"),
+            Some((U256::from(1000), U256::zero()))
+        );
+        assert_eq!(free(&context, account_a, key_b), before);
+        let cancel = session(
+            account_a,
+            new_hash,
+            "cancel_proposal",
+            runtime_args! {},
+            16 * DAY,
+        );
+        context.run(cancel.build());
+        let claim = session(
+            account_a,
+            new_hash,
+            "claim",
+            runtime_args! {"id" => 9u64},
+            16 * DAY,
+        );
+        context.run(claim.build());
+        println!("User b could not pledge more than 60% of vote_limit");
+
+        // user c burns 1000 of its own token, a proposal burns 1000000 of the reserve
+        let before = (
+            free(&context, account_a, key_c),
+            read::<U256>(&context, account_a, "supply"),
+            read::<U256>(&context, account_a, "total_supply"),
+        );
+        let burn = session(
+            account_c,
+            new_hash,
+            "burn",
+            runtime_args! {"amount" => U256::from(1000)},
+            17 * DAY,
+        );
+        context.run(burn.build());
+        assert_eq!(
+            free(&context, account_a, key_c),
+            before.0 - U256::from(1000)
+        );
+        pass(
+            &mut context,
+            accounts,
+            new_hash,
+            "burn 1000000".into(),
+            17 * DAY,
+        );
+        // supply paid the reward of 1000 too
+        assert_eq!(
+            read::<U256>(&context, account_a, "supply"),
+            before.1 - U256::from(1_001_000)
+        );
+        assert_eq!(
+            read::<U256>(&context, account_a, "total_supply"),
+            before.2 - U256::from(1_001_000)
+        );
+        assert_eq!(
+            read::<U256>(&context, account_a, "total_burned"),
+            U256::from(1_001_000)
+        );
+        println!("1001000 token was burned");
+
+        // a revocable grant of 1000000 token for user c,
+        // it vests in ten days after a cliff of one day and is locked until released
+        let before = free(&context, account_a, key_c);
+        let grant = format!(
+            "vesting {} 1000000 {} {} {} revocable",
+            account_c.to_formatted_string(),
+            18 * DAY,
+            DAY,
+            10 * DAY
+        );
+        pass(&mut context, accounts, new_hash, grant, 18 * DAY);
+        assert_eq!(free(&context, account_a, key_c), before);
+        let release = session(
+            account_c,
+            new_hash,
+            "release",
+            runtime_args! {"id" => 0u64},
+            23 * DAY,
+        );
+        context.run(release.build());
+        assert_eq!(
+            free(&context, account_a, key_c),
+            before + U256::from(500_000)
+        );
+
+        // revoking it gives the half which has not vested back to supply,
+        // which paid the reward of 1000
+        let supply: U256 = read(&context, account_a, "supply");
+        pass(
+            &mut context,
+            accounts,
+            new_hash,
+            "revoke 0".into(),
+            23 * DAY,
+        );
+        assert_eq!(
+            read::<U256>(&context, account_a, "supply"),
+            supply + U256::from(499_000)
+        );
+        let vestings: Vec<Vesting> = read(&context, account_a, "vestings");
+        assert_eq!(
+            vestings[0].1,
+            (key_c, U256::from(500_000), U256::from(500_000))
+        );
+        println!("User c released half of its grant, the other half was revoked");
+
+        // a token sale at 1000 motes per token, 5000 token for one account and 8000 in all
+        for (key, value) in [
+            ("sale_price", 1000),
+            ("sale_account_cap", 5000),
+            ("sale_cap", 8000),
+            ("sale_end", 100 * DAY),
+        ]
+        .iter()
+        {
+            let update = format!("update {} {}", key, value);
+            pass(&mut context, accounts, new_hash, update, 24 * DAY);
+        }
+        let voter_purse: URef = context
+            .query(account_a, &["voter_purse".into()])
+            .unwrap()
+            .into_t()
+            .unwrap();
+        let before = (
+            free(&context, account_a, voter),
+            context.get_balance(voter_purse.addr()),
+        );
+        // the contract has CSPR for far more, it gets the account cap
+        let buy = session(
+            account_b,
+            voter_hash,
+            "buy_tokens",
+            runtime_args! {},
+            24 * DAY,
+        );
+        context.run(buy.build());
+        assert_eq!(
+            free(&context, account_a, voter),
+            before.0 + U256::from(5000)
+        );
+        assert_eq!(
+            context.get_balance(voter_purse.addr()),
+            before.1 - U512::from(5_000_000)
+        );
+        let buy = session(
+            account_b,
+            voter_hash,
+            "buy_tokens",
+            runtime_args! {},
+            24 * DAY,
+        );
+        context.run(buy.without_expect_success().build());
+        assert_eq!(
+            free(&context, account_a, voter),
+            before.0 + U256::from(5000)
+        );
+        // with a bigger account cap it gets what is left of the sale cap
+        pass(
+            &mut context,
+            accounts,
+            new_hash,
+            "update sale_account_cap 10000".into(),
+            24 * DAY,
+        );
+        let buy = session(
+            account_b,
+            voter_hash,
+            "buy_tokens",
+            runtime_args! {},
+            24 * DAY,
+        );
+        context.run(buy.build());
+        assert_eq!(
+            free(&context, account_a, voter),
+            before.0 + U256::from(8000)
+        );
+        assert_eq!(
+            context.get_balance(voter_purse.addr()),
+            before.1 - U512::from(8_000_000)
+        );
+        println!("The contract bought 8000 token in the sale");
+
+        // on a linear bonding curve the contract buys 100 token and sells them back,
+        // both at the quote of the DAO
+        pass(
+            &mut context,
+            accounts,
+            new_hash,
+            "update curve_mode 1".into(),
+            25 * DAY,
+        );
+        let before = (
+            free(&context, account_a, voter),
+            context.get_balance(voter_purse.addr()),
+            read::<U256>(&context, account_a, "supply"),
+        );
+        let quote = session(
+            account_b,
+            voter_hash,
+            "quote",
+            runtime_args! {"entry" => String::from("quote_buy"), "amount" => U256::from(100)},
+            25 * DAY,
+        );
+        context.run(quote.build());
+        let cost: U256 = context
+            .query(account_a, &["voter_quote".into()])
+            .unwrap()
+            .into_t()
+            .unwrap();
+        // 1 CSPR per token plus 1000 motes for every token issued before it
+        assert_eq!(cost, U256::from(100_005_000_000u64));
+        let buy = session(
+            account_b,
+            voter_hash,
+            "buy_on_curve",
+            runtime_args! {"amount" => U256::from(100)},
+            25 * DAY,
+        );
+        context.run(buy.build());
+        assert_eq!(free(&context, account_a, voter), before.0 + U256::from(100));
+        assert_eq!(
+            context.get_balance(voter_purse.addr()),
+            before.1 - U512::from(cost.as_u128())
+        );
+
+        let quote = session(
+            account_b,
+            voter_hash,
+            "quote",
+            runtime_args! {"entry" => String::from("quote_sell"), "amount" => U256::from(100)},
+            25 * DAY,
+        );
+        context.run(quote.build());
+        let refund: U256 = context
+            .query(account_a, &["voter_quote".into()])
+            .unwrap()
+            .into_t()
+            .unwrap();
+        assert_eq!(refund, cost);
+        let sell = session(
+            account_b,
+            voter_hash,
+            "sell_on_curve",
+            runtime_args! {"amount" => U256::from(100)},
+            25 * DAY,
+        );
+        context.run(sell.build());
+        assert_eq!(free(&context, account_a, voter), before.0);
+        assert_eq!(context.get_balance(voter_purse.addr()), before.1);
+        assert_eq!(read::<U256>(&context, account_a, "supply"), before.2);
+        assert_eq!(
+            read::<U256>(&context, account_a, "curve_reserve"),
+            U256::zero()
+        );
+        println!("The contract bought and sold 100 token on the curve");
+
+        // nobody can ragequit for a day after a mint passed
+        pass(
+            &mut context,
+            accounts,
+            new_hash,
+            format!("mint 1 {}", account_c.to_formatted_string()),
+            26 * DAY,
+        );
+        let before = (
+            free(&context, account_a, voter),
+            context.get_balance(voter_purse.addr()),
+            read::<U256>(&context, account_a, "total_supply"),
+        );
+        let ragequit = session(
+            account_b,
+            voter_hash,
+            "ragequit",
+            runtime_args! {"amount" => U256::from(300_000)},
+            26 * DAY,
+        );
+        context.run(ragequit.without_expect_success().build());
+        assert_eq!(free(&context, account_a, voter), before.0);
+        assert_eq!(context.get_balance(voter_purse.addr()), before.1);
+
+        // a day later the contract burns 300000 token for the same share of the
+        // 8000000 motes the sale paid into the treasury, the curve reserve is empty
+        let circulating = before.2 - read::<U256>(&context, account_a, "supply");
+        let payout = U256::from(8_000_000) * 300_000 / circulating;
+        assert!(!payout.is_zero());
+        let ragequit = session(
+            account_b,
+            voter_hash,
+            "ragequit",
+            runtime_args! {"amount" => U256::from(300_000)},
+            27 * DAY,
+        );
+        context.run(ragequit.build());
+        assert_eq!(
+            free(&context, account_a, voter),
+            before.0 - U256::from(300_000)
+        );
+        assert_eq!(
+            context.get_balance(voter_purse.addr()),
+            before.1 + U512::from(payout.as_u128())
+        );
+        assert_eq!(
+            read::<U256>(&context, account_a, "total_supply"),
+            before.2 - U256::from(300_000)
+        );
+        println!("The contract ragequit with its share of the treasury");
+    }
+
+    #[test]
+    fn founder_vesting() {
+        let (mut context, [account_a, account_b, _], hash) = create();
+
+        // a plan which is not "{supply} {cliff} {duration}", or with the cliff after the end, is rejected
+        for plan in [
+            String::from("100000000 1 2 3"),
+            format!("100000000 {}", DAY),
+            format!("100000000 {} {}", 10 * DAY, DAY),
+        ]
+        .iter()
+        {
+            let proposal = session(
+                account_a,
+                hash,
+                "proposal",
+                runtime_args! {"plan" => plan.clone()},
+                0,
+            );
+            context.run(proposal.without_expect_success().build());
+            let plan: String = context
+                .query(account_a, &["plan".into()])
+                .unwrap()
+                .into_t()
+                .unwrap();
+            assert_eq!(plan, "".to_string());
+        }
+
+        // the 10000000 token of every original vests in ten days after a cliff of one day
+        let proposal = session(
+            account_a,
+            hash,
+            "proposal",
+            runtime_args! {"plan" => format!("100000000 {} {}", DAY, 10 * DAY)},
+            0,
+        );
+        context.run(proposal.build());
+        let vote = session(account_b, hash, "vote", runtime_args! {"vote" => true}, 0);
+        context.run(vote.build());
+        let new_hash: ContractHash = context
+            .query(account_a, &["DAO_contract_hash".into()])
+            .unwrap()
+            .into_t()
+            .unwrap();
+        let key_a = Key::Account(account_a);
+        assert_eq!(free(&context, account_a, key_a), U256::zero());
+
+        // nothing is released before the cliff, half of it after five days
+        let release = session(
+            account_a,
+            new_hash,
+            "release",
+            runtime_args! {"id" => 0u64},
+            HOUR,
+        );
+        context.run(release.without_expect_success().build());
+        assert_eq!(free(&context, account_a, key_a), U256::zero());
+        let release = session(
+            account_a,
+            new_hash,
+            "release",
+            runtime_args! {"id" => 0u64},
+            5 * DAY,
+        );
+        context.run(release.build());
+        assert_eq!(free(&context, account_a, key_a), U256::from(5_000_000));
+    }
+
+    #[test]
+    fn membership() {
+        let (mut context, [account_a, account_b, account_c], dao, voter_hash) = online("100000000");
+        let account_d = PublicKey::ed25519_from_bytes(&ACCOUNT_D)
+            .unwrap()
+            .to_account_hash();
+        let admit_d = format!("admit {}", account_d.to_formatted_string());
+
+        // half of the three originals have to vote, the upvote of user b decides it at once
+        let proposal = session(
+            account_a,
+            dao,
+            "new_member_proposal",
+            runtime_args! {"proposal" => admit_d},
+            0,
+        );
+        context.run(proposal.build());
+        let vote = session(
+            account_b,
+            dao,
+            "vote_as_member",
+            runtime_args! {"vote" => true},
+            0,
+        );
+        context.run(vote.build());
+        let members: Vec<AccountHash> = read(&context, account_a, "members");
+        assert_eq!(members.len(), 4);
+        assert!(members.contains(&account_d));
+        assert!(is_member(&mut context, account_a, voter_hash, account_d, 0));
+
+        // admitting a member again changes nothing, so no event is emitted
+        let events: u32 = context
+            .query(account_a, &["events_length".into()])
+            .unwrap()
+            .into_t()
+            .unwrap();
+        let proposal = session(
+            account_a,
+            dao,
+            "new_member_proposal",
+            runtime_args! {"proposal" => format!("admit {}", account_b.to_formatted_string())},
+            HOUR,
+        );
+        context.run(proposal.build());
+        for account in [account_b, account_c].iter() {
+            let vote = session(
+                *account,
+                dao,
+                "vote_as_member",
+                runtime_args! {"vote" => true},
+                HOUR,
+            );
+            context.run(vote.build());
+        }
+        assert_eq!(
+            read::<Vec<AccountHash>>(&context, account_a, "members").len(),
+            4
+        );
+        let length: u32 = context
+            .query(account_a, &["events_length".into()])
+            .unwrap()
+            .into_t()
+            .unwrap();
+        assert_eq!(length, events);
+
+        // user d votes against its own expulsion, three of the four members outvote it
+        let proposal = session(
+            account_b,
+            dao,
+            "new_member_proposal",
+            runtime_args! {"proposal" => format!("expel {}", account_d.to_formatted_string())},
+            2 * HOUR,
+        );
+        context.run(proposal.build());
+        for (account, vote) in [(account_d, false), (account_a, true), (account_c, true)].iter() {
+            let vote = session(
+                *account,
+                dao,
+                "vote_as_member",
+                runtime_args! {"vote" => *vote},
+                2 * HOUR,
+            );
+            context.run(vote.build());
+        }
+        let members: Vec<AccountHash> = read(&context, account_a, "members");
+        assert_eq!(members.len(), 3);
+        assert!(!members.contains(&account_d));
+        assert!(!is_member(
+            &mut context,
+            account_a,
+            voter_hash,
+            account_d,
+            2 * HOUR
+        ));
+
+        // and it can not propose any more
+        let proposal = session(
+            account_d,
+            dao,
+            "new_member_proposal",
+            runtime_args! {"proposal" => format!("admit {}", account_d.to_formatted_string())},
+            3 * HOUR,
+        );
+        context.run(proposal.without_expect_success().build());
+        assert_eq!(
+            read::<Option<String>>(&context, account_a, "member_proposal"),
+            None
+        );
+
+        // a tie is not decided before the deadline, after it the proposal fails
+        let proposal = session(
+            account_a,
+            dao,
+            "new_member_proposal",
+            runtime_args! {"proposal" => format!("expel {}", account_c.to_formatted_string())},
+            3 * HOUR,
+        );
+        context.run(proposal.build());
+        let vote = session(
+            account_b,
+            dao,
+            "vote_as_member",
+            runtime_args! {"vote" => false},
+            3 * HOUR,
+        );
+        context.run(vote.build());
+        let finalize = session(
+            account_c,
+            dao,
+            "finalize_member_proposal",
+            runtime_args! {},
+            4 * HOUR,
+        );
+        context.run(finalize.without_expect_success().build());
+        assert!(read::<Option<String>>(&context, account_a, "member_proposal").is_some());
+        let finalize = session(
+            account_c,
+            dao,
+            "finalize_member_proposal",
+            runtime_args! {},
+            3 * HOUR + 3 * DAY,
+        );
+        context.run(finalize.build());
+        assert_eq!(
+            read::<Option<String>>(&context, account_a, "member_proposal"),
+            None
+        );
+        assert!(read::<Vec<AccountHash>>(&context, account_a, "members").contains(&account_c));
+    }
+
+    #[test]
+    fn snapshot() {
+        let (mut context, [account_a, account_b, account_c], dao, voter_hash) = online("100000000");
+        let key_b = Key::Account(account_b);
+
+        // the snapshot of a proposal is the block time it was created at
+        let proposal = session(
+            account_a,
+            dao,
+            "new_proposal",
+            runtime_args! {
+            "proposal" => String::from("update reward 1"),
+            "vote_limit" => U256::from(10000),
+            "limit_in_bps" => true,
+            "vote" => true,
+            "amount" => U256::from(1000)},
+            HOUR,
+        );
+        context.run(proposal.build());
+        let sponsor = session(
+            account_c,
+            dao,
+            "sponsor_proposal",
+            runtime_args! {"id" => 0u64},
+            HOUR,
+        );
+        context.run(sponsor.build());
+
+        // user b gets 5000000 token after the snapshot
+        let transfer = session(
+            account_a,
+            dao,
+            "transfer",
+            runtime_args! {"amount" => U256::from(5_000_000), "recipient" => key_b},
+            2 * HOUR,
+        );
+        context.run(transfer.build());
+        for (time, balance) in [(HOUR, 10_000_000u64), (2 * HOUR, 15_000_000)].iter() {
+            let ask = session(
+                account_a,
+                voter_hash,
+                "balance_of_at",
+                runtime_args! {"account" => key_b, "time" => *time},
+                2 * HOUR,
+            );
+            context.run(ask.build());
+            let answer: U256 = context
+                .query(account_a, &["voter_answer".into()])
+                .unwrap()
+                .into_t()
+                .unwrap();
+            assert_eq!(answer, U256::from(*balance));
+        }
+
+        // so it can pledge the 10000000 it owned at the snapshot, not more
+        let pledge = session(
+            account_b,
+            dao,
+            "vote_by_pledges",
+            runtime_args! {"vote" => true, "amount" => U256::from(10_000_001)},
+            2 * HOUR,
+        );
+        context.run(pledge.without_expect_success().build());
+        assert_eq!(
+            read::<Option<(U256, U256)>>(&context, account_a, "pool"),
+            Some((U256::from(1000), U256::zero()))
+        );
+        let pledge = session(
+            account_b,
+            dao,
+            "vote_by_pledges",
+            runtime_args! {"vote" => true, "amount" => U256::from(10_000_000)},
+            2 * HOUR,
+        );
+        context.run(pledge.build());
+        assert_eq!(
+            read::<Option<(U256, U256)>>(&context, account_a, "pool"),
+            Some((U256::from(10_001_000), U256::zero()))
+        );
+
+        // the pledge is still owned by user b, but not free
+        for (entry, balance) in [
+            ("balance_of", 15_000_000u64),
+            ("free_balance_of", 5_000_000),
+        ]
+        .iter()
+        {
+            let ask = session(
+                account_a,
+                voter_hash,
+                "ask",
+                runtime_args! {"entry" => entry.to_string(), "account" => key_b},
+                2 * HOUR,
+            );
+            context.run(ask.build());
+            let answer: U256 = context
+                .query(account_a, &["voter_answer".into()])
+                .unwrap()
+                .into_t()
+                .unwrap();
+            assert_eq!(answer, U256::from(*balance));
+        }
+    }
+
+    #[test]
+    fn claim_twice() {
+        let (mut context, accounts, dao, _) = online("100000000");
+        let account_a = accounts[0];
+        let key_a = Key::Account(account_a);
+        pass(
+            &mut context,
+            accounts,
+            dao,
+            String::from("update reward 1"),
+            0,
+        );
+
+        // the first claim cleared the pledges of user a, a second one reverts
+        let free_a = free(&context, account_a, key_a);
+        let claim = session(account_a, dao, "claim", runtime_args! {"id" => 0u64}, HOUR);
+        context.run(claim.without_expect_success().build());
+        assert_eq!(free(&context, account_a, key_a), free_a);
+    }
+
+    #[test]
+    fn failed_execution() {
+        let (mut context, accounts, dao, _) = online("100000000");
+        let [account_a, account_b, account_c] = accounts;
+        let (key_a, key_b) = (Key::Account(account_a), Key::Account(account_b));
+
+        // minting more than `supply` passes, but can not run
+        let supply: U256 = read(&context, account_a, "supply");
+        let reward: U256 = read(&context, account_a, "reward");
+        let id = decide(
+            &mut context,
+            accounts,
+            dao,
+            format!("mint {} {}", supply + 1, account_c.to_formatted_string()),
+            0,
+        );
+        let ((passed, executed, _), (pool, (reward_pool, refund)), _): Outcome =
+            item(&context, dao, "results", &id.to_string());
+        assert!(passed);
+        assert!(!executed);
+        // the tally and the reward pool are kept as if it ran
+        let half = U256::from(1_500_000);
+        assert_eq!(pool, (half + half, U256::zero()));
+        assert_eq!(reward_pool, reward);
+        assert_eq!(refund, U256::zero());
+        assert_eq!(read::<U256>(&context, account_a, "supply"), supply - reward);
+
+        // and both voters claim their pledges and share of the reward
+        let before = (
+            free(&context, account_a, key_a),
+            free(&context, account_b, key_b),
+        );
+        for account in [account_a, account_b].iter() {
+            let claim = session(*account, dao, "claim", runtime_args! {"id" => id}, HOUR);
+            context.run(claim.build());
+        }
+        let share = reward * half / (half + half);
+        assert_eq!(free(&context, account_a, key_a), before.0 + half + share);
+        assert_eq!(
+            free(&context, account_b, key_b),
+            before.1 + half + (reward - share)
+        );
+        let (_, (left, (reward_left, _)), _): Outcome =
+            item(&context, dao, "results", &id.to_string());
+        assert_eq!(left, (U256::zero(), U256::zero()));
+        assert_eq!(reward_left, U256::zero());
+    }
+
+    #[test]
+    fn proposal_events() {
+        let (mut context, accounts, dao, _) = online("100000000");
+        let account_a = accounts[0];
+        let id = decide(
+            &mut context,
+            accounts,
+            dao,
+            String::from("update reward 1"),
+            0,
+        );
+
+        // created, sponsored, opened with the creator's vote, voted by user b and resolved
+        let events = read_events(&context, account_a);
+        let start = events
+            .iter()
+            .position(|(name, _)| name == "event_ProposalCreated")
+            .unwrap();
+        let names: Vec<&str> = events[start..]
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "event_ProposalCreated",
+                "event_Sponsored",
+                "event_ProposalOpened",
+                "event_Voted",
+                "event_Voted",
+                "event_ProposalResolved"
+            ]
+        );
+
+        // Voted is (id, voter, vote, amount)
+        let (_, fields) = &events[start + 3];
+        let (voted, rest) = u64::from_bytes(fields).unwrap();
+        let (voter, rest) = Key::from_bytes(rest).unwrap();
+        let (vote, rest) = bool::from_bytes(rest).unwrap();
+        let (amount, rest) = U256::from_bytes(rest).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(
+            (voted, voter, vote, amount),
+            (id, Key::Account(account_a), true, U256::from(1_500_000))
+        );
+
+        // ProposalResolved is (id, passed, executed, closed_by), 0 is closed by vote_limit
+        let (_, fields) = &events[start + 5];
+        let (resolved, rest) = u64::from_bytes(fields).unwrap();
+        let (passed, rest) = bool::from_bytes(rest).unwrap();
+        let (executed, rest) = bool::from_bytes(rest).unwrap();
+        let (closed_by, rest) = u8::from_bytes(rest).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!((resolved, passed, executed, closed_by), (id, true, true, 0));
+    }
+
+    // deploy the DAO and let the other two originals join, it waits for a plan then
+    fn create() -> (TestContext, [AccountHash; 3], ContractHash) {
         // Prepare Account
         let pub_a = PublicKey::ed25519_from_bytes(&ACCOUNT_A).unwrap();
         let pub_b = PublicKey::ed25519_from_bytes(&ACCOUNT_B).unwrap();
         let pub_c = PublicKey::ed25519_from_bytes(&ACCOUNT_C).unwrap();
+        let pub_d = PublicKey::ed25519_from_bytes(&ACCOUNT_D).unwrap();
 
         let account_a = pub_a.to_account_hash();
         let account_b = pub_b.to_account_hash();
@@ -27,6 +1449,7 @@ mod tests {
             .with_public_key(pub_a, U512::from(100_000_000_000_000u64))
             .with_public_key(pub_b, U512::from(100_000_000_000_000u64))
             .with_public_key(pub_c, U512::from(100_000_000_000_000u64))
+            .with_public_key(pub_d, U512::from(100_000_000_000_000u64))
             .build();
 
         println!("prepare finished");
@@ -86,106 +1509,204 @@ mod tests {
             .into_t()
             .unwrap();
         assert_eq!(status, "plan".to_string());
+        (context, [account_a, account_b, account_c], hash)
+    }
 
-        // proposal for plan
-        let proposal_code = Code::Hash(hash.value(), "proposal".into());
-        let proposal = SessionBuilder::new(
-            proposal_code,
-            runtime_args! {"plan" => String::from("100000000")},
+    // put `plan` online and install the voter helper, which gives user a the `dao_contract` key `read` uses
+    fn online(plan: &str) -> (TestContext, [AccountHash; 3], ContractHash, ContractHash) {
+        let (mut context, accounts, hash) = create();
+        let [account_a, account_b, _] = accounts;
+        let proposal = session(
+            account_a,
+            hash,
+            "proposal",
+            runtime_args! {"plan" => plan.to_string()},
+            0,
+        );
+        context.run(proposal.build());
+        let vote = session(account_b, hash, "vote", runtime_args! {"vote" => true}, 0);
+        context.run(vote.build());
+        let dao: ContractHash = context
+            .query(account_a, &["DAO_contract_hash".into()])
+            .unwrap()
+            .into_t()
+            .unwrap();
+        let voter = SessionBuilder::new(
+            Code::from("voter.wasm"),
+            runtime_args! {"dao" => dao, "motes" => U512::from(1_000_000_000_000u64)},
         )
         .with_address(account_a)
         .with_authorization_keys(&[account_a])
         .build();
-        context.run(proposal);
-
-        // vote for plan
-        // when upvote hit to two people, plan goes online
-        let vote_code = Code::Hash(hash.value(), "vote".into());
-        let vote = SessionBuilder::new(vote_code, runtime_args! {"vote" => true})
-            .with_address(account_b)
-            .with_authorization_keys(&[account_b])
-            .build();
-        context.run(vote);
-
-        let status: String = context
-            .query(account_a, &["status".into()])
+        context.run(voter);
+        let voter_hash: ContractHash = context
+            .query(account_a, &["voter_hash".into()])
             .unwrap()
             .into_t()
             .unwrap();
-        assert_eq!(status, "online".to_string());
+        (context, accounts, dao, voter_hash)
+    }
 
-        // now it's online
-        // And we can get online contract hash for further call
-        let new_hash: ContractHash = context
-            .query(account_a, &["DAO_contract_hash".into()])
-            .unwrap()
-            .into_t()
-            .unwrap();
+    // a call of `entry` on `contract` by `account` at block `time`
+    fn session(
+        account: AccountHash,
+        contract: ContractHash,
+        entry: &str,
+        args: RuntimeArgs,
+        time: u64,
+    ) -> SessionBuilder {
+        SessionBuilder::new(Code::Hash(contract.value(), entry.into()), args)
+            .with_address(account)
+            .with_authorization_keys(&[account])
+            .with_block_time(time)
+    }
 
-        println!("Now DAO is online");
+    // run a proposal through at `time` and return its id: user a proposes with
+    // half of vote_limit, user c sponsors, user b pledges the rest and user c finalizes it.
+    // vote_limit is a tenth of the circulating supply, the quorum of a constitutional proposal
+    fn decide(
+        context: &mut TestContext,
+        accounts: [AccountHash; 3],
+        dao: ContractHash,
+        proposal: String,
+        time: u64,
+    ) -> u64 {
+        let [account_a, account_b, account_c] = accounts;
+        let circulating = read::<U256>(context, account_a, "total_supply")
+            - read::<U256>(context, account_a, "supply");
+        let vote_limit = circulating / 10;
+        let id: u64 = read(context, account_a, "next_proposal_id");
+        let proposal = session(
+            account_a,
+            dao,
+            "new_proposal",
+            runtime_args! {
+            "proposal" => proposal,
+            "vote_limit" => vote_limit,
+            "limit_in_bps" => false,
+            "vote" => true,
+            "amount" => vote_limit / 2},
+            time,
+        );
+        context.run(proposal.build());
+        let sponsor = session(
+            account_c,
+            dao,
+            "sponsor_proposal",
+            runtime_args! {"id" => id},
+            time,
+        );
+        context.run(sponsor.build());
+        let pledge = session(
+            account_b,
+            dao,
+            "vote_by_pledges",
+            runtime_args! {"vote" => true, "amount" => vote_limit - vote_limit / 2},
+            time,
+        );
+        context.run(pledge.build());
+        let finalize = session(account_c, dao, "finalize_proposal", runtime_args! {}, time);
+        context.run(finalize.build());
+        id
+    }
 
-        // this will show every originals account have `10000000` token
+    // decide a proposal, both voters claim their pledges and reward
+    fn pass(
+        context: &mut TestContext,
+        accounts: [AccountHash; 3],
+        dao: ContractHash,
+        proposal: String,
+        time: u64,
+    ) {
+        let id = decide(context, accounts, dao, proposal, time);
+        let [account_a, account_b, _] = accounts;
+        for account in [account_a, account_b].iter() {
+            let claim = session(*account, dao, "claim", runtime_args! {"id" => id}, time);
+            context.run(claim.build());
+        }
+    }
+
+    // a named key of the online DAO, the voter helper put it into `owner` as `dao_contract`
+    fn read<T: CLTyped + FromBytes>(context: &TestContext, owner: AccountHash, name: &str) -> T {
         context
-            .query(account_a, &["accounting".into()])
+            .query(owner, &["dao_contract".into(), name.into()])
             .unwrap()
-            .into_t::<Vec<(AccountHash, U256)>>()
-            .unwrap().into_iter().enumerate().map(|(index,(_,f))|{
-                println!("User {}: {}",&index,f)
-            }).count();
-
-        // now create a proposal that will udpate reward to two, and user a will upvote this vote with pledge 1 token
-        let proposal_code = Code::Hash(new_hash.value(), "new_proposal".into());
-        let proposal = SessionBuilder::new(
-            proposal_code,
-            runtime_args! {   "proposal"=> String::from("update reward 2"),
-            "vote_limit"=> U256::from(20),
-            "vote" => true,
-            "amount"=> U256::from(1)},
-        )
-        .with_address(account_a)
-        .with_authorization_keys(&[account_a])
-        .build();
-        context.run(proposal);
-
-        println!("Now first proposal is create by uesr a, and user a vote upvote with amount 1. this proposal will set reward to 2");
+            .into_t()
+            .unwrap()
+    }
 
-        // after create proposal, this will show the account_a only have `9999999` token now
+    // an item of a dictionary of the online DAO
+    fn item<T: CLTyped + FromBytes>(
+        context: &TestContext,
+        dao: ContractHash,
+        dictionary: &str,
+        item: &str,
+    ) -> T {
         context
-            .query(account_a, &["accounting".into()])
+            .query_dictionary_item(Key::Hash(dao.value()), Some(dictionary.into()), item.into())
             .unwrap()
-            .into_t::<Vec<(AccountHash, U256)>>()
-            .unwrap().into_iter().enumerate().map(|(index,(_,f))|{
-                println!("User {}: {}",&index,f)
-            }).count();
+            .into_t()
+            .unwrap()
+    }
 
-        // other user pledge token for vote
-        // user b pledge 20 token this will hit limit to make contract decide execute the proposal or not.
-        // and after execute proposal. user will get back their money with reward
-        let vote_code = Code::Hash(new_hash.value(), "vote_by_pledges".into());
-        let vote = SessionBuilder::new(
-            vote_code,
-            runtime_args! {
-            "vote" => true,
-            "amount"=> U256::from(20)},
-        )
-        .with_address(account_b)
-        .with_authorization_keys(&[account_b])
-        .build();
-        context.run(vote);
+    // every event in the `events` dictionary of `owner` as (name, bytes of the fields)
+    fn read_events(context: &TestContext, owner: AccountHash) -> Vec<(String, Vec<u8>)> {
+        let length: u32 = context
+            .query(owner, &["events_length".into()])
+            .unwrap()
+            .into_t()
+            .unwrap();
+        (0..length)
+            .map(|index| {
+                let event: Bytes = context
+                    .query_dictionary_item(
+                        Key::Account(owner),
+                        Some("events".into()),
+                        index.to_string(),
+                    )
+                    .unwrap()
+                    .into_t()
+                    .unwrap();
+                let (name, fields) = String::from_bytes(&event).unwrap();
+                (name, fields.to_vec())
+            })
+            .collect()
+    }
 
-        println!("Now another user voted, and proposal hit the limit of vote, it will be executed. And users' money will back with reward");
+    // ask the DAO through the voter helper whether `account` is a member at `time`
+    fn is_member(
+        context: &mut TestContext,
+        owner: AccountHash,
+        voter: ContractHash,
+        account: AccountHash,
+        time: u64,
+    ) -> bool {
+        let ask = session(
+            owner,
+            voter,
+            "is_member",
+            runtime_args! {"account" => Key::Account(account)},
+            time,
+        );
+        context.run(ask.build());
+        context
+            .query(owner, &["voter_member".into()])
+            .unwrap()
+            .into_t()
+            .unwrap()
+    }
 
-        // so this will show after the reward been setting to 2.
-        // user will get back their money with reward two.
-        // So now user_a have 10000002,
-        // user _b have 10000002 too
+    // free token of a holder
+    fn free(context: &TestContext, owner: AccountHash, holder: Key) -> U256 {
         context
-            .query(account_a, &["accounting".into()])
+            .query(owner, &["accounting".into()])
             .unwrap()
-            .into_t::<Vec<(AccountHash, U256)>>()
-            .unwrap().into_iter().enumerate().map(|(index,(_,f))|{
-                println!("User {}: {}",&index,f)
-            }).count();
+            .into_t::<Vec<(Key, U256)>>()
+            .unwrap()
+            .into_iter()
+            .find(|(key, _)| key == &holder)
+            .map(|(_, balance)| balance)
+            .unwrap_or_default()
     }
 }
 
@@ -0,0 +1,282 @@
+#![no_std]
+#![no_main]
+
+#[cfg(not(target_arch = "wasm32"))]
+compile_error!("target arch should be wasm32: compile with '--target wasm32-unknown-unknown'");
+
+// A small contract which holds DAO token and votes with it, buys and sells
+// token with CSPR from its own purse and ragequits into it, and reads the
+// DAO through its query entry points.
+// Used by the integration tests to check that the DAO attributes calls from
+// a contract to the contract's own balance.
+extern crate alloc;
+
+use alloc::{string::String, vec};
+
+use casper_contract::contract_api::{account, runtime, storage, system};
+use casper_types::{
+    contracts::NamedKeys, runtime_args, CLType, ContractHash, EntryPoint, EntryPointAccess,
+    EntryPointType, EntryPoints, Key, Parameter, RuntimeArgs, URef, U256, U512,
+};
+
+#[no_mangle]
+pub extern "C" fn call() {
+    // ============
+    // args:
+    // dao: ContractHash (the online DAO contract)
+    // motes: U512 (CSPR moved from the account into the purse of the contract)
+    // ============
+    // Variable on the storage:
+    // - voter_package: ContractPackageHash
+    // - voter_hash: ContractHash
+    // - voter_holder: Key (the key the DAO keeps the token of this contract under)
+    // - dao_contract: Key (the DAO itself, so the tests can read its named keys)
+    // - voter_purse: URef (the purse of the contract, so the tests can read its balance)
+    // - voter_quote: U256 (the last quote the contract asked the DAO for)
+    // - voter_answer: U256 (the last balance the contract asked the DAO for)
+    // - voter_member: bool (the last membership the contract asked the DAO for)
+    let dao: ContractHash = runtime::get_named_arg("dao");
+    runtime::put_key("dao_contract", Key::Hash(dao.value()));
+    let mut keys = NamedKeys::new();
+    keys.insert("dao".into(), Key::URef(storage::new_uref(dao)));
+
+    let motes: U512 = runtime::get_named_arg("motes");
+    let purse = system::create_purse();
+    system::transfer_from_purse_to_purse(account::get_main_purse(), purse, motes, None).unwrap();
+    keys.insert("purse".into(), Key::URef(purse));
+    runtime::put_key("voter_purse", Key::URef(storage::new_uref(purse)));
+    let quote = storage::new_uref(U256::zero());
+    keys.insert("quote".into(), Key::URef(quote));
+    runtime::put_key("voter_quote", Key::URef(quote));
+    let answer = storage::new_uref(U256::zero());
+    keys.insert("answer".into(), Key::URef(answer));
+    runtime::put_key("voter_answer", Key::URef(answer));
+    let member = storage::new_uref(false);
+    keys.insert("member".into(), Key::URef(member));
+    runtime::put_key("voter_member", Key::URef(member));
+
+    let mut entries = EntryPoints::new();
+    entries.add_entry_point(EntryPoint::new(
+        "new_proposal",
+        vec![
+            Parameter::new("proposal", CLType::String),
+            Parameter::new("vote_limit", CLType::U256),
+            Parameter::new("limit_in_bps", CLType::Bool),
+            Parameter::new("vote", CLType::Bool),
+            Parameter::new("amount", CLType::U256),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entries.add_entry_point(EntryPoint::new(
+        "vote_by_pledges",
+        vec![
+            Parameter::new("vote", CLType::Bool),
+            Parameter::new("amount", CLType::U256),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entries.add_entry_point(EntryPoint::new(
+        "claim",
+        vec![Parameter::new("id", CLType::U64)],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entries.add_entry_point(EntryPoint::new(
+        "buy_tokens",
+        vec![],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    for name in ["buy_on_curve", "sell_on_curve", "ragequit"].iter() {
+        entries.add_entry_point(EntryPoint::new(
+            *name,
+            vec![Parameter::new("amount", CLType::U256)],
+            CLType::Unit,
+            EntryPointAccess::Public,
+            EntryPointType::Contract,
+        ));
+    }
+    entries.add_entry_point(EntryPoint::new(
+        "quote",
+        vec![
+            Parameter::new("entry", CLType::String),
+            Parameter::new("amount", CLType::U256),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entries.add_entry_point(EntryPoint::new(
+        "ask",
+        vec![
+            Parameter::new("entry", CLType::String),
+            Parameter::new("account", CLType::Key),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entries.add_entry_point(EntryPoint::new(
+        "is_member",
+        vec![Parameter::new("account", CLType::Key)],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+    entries.add_entry_point(EntryPoint::new(
+        "balance_of_at",
+        vec![
+            Parameter::new("account", CLType::Key),
+            Parameter::new("time", CLType::U64),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    let (hash, _) = storage::new_contract(
+        entries,
+        Some(keys),
+        Some("voter_package".into()),
+        Some("voter_access".into()),
+    );
+    runtime::put_key("voter_hash", Key::URef(storage::new_uref(hash)));
+    let holder = runtime::get_key("voter_package").unwrap();
+    runtime::put_key("voter_holder", Key::URef(storage::new_uref(holder)));
+}
+
+#[no_mangle]
+pub extern "C" fn new_proposal() {
+    let proposal: String = runtime::get_named_arg("proposal");
+    let vote_limit: U256 = runtime::get_named_arg("vote_limit");
+    let limit_in_bps: bool = runtime::get_named_arg("limit_in_bps");
+    let vote: bool = runtime::get_named_arg("vote");
+    let amount: U256 = runtime::get_named_arg("amount");
+    runtime::call_contract::<()>(
+        dao(),
+        "new_proposal",
+        runtime_args! {
+            "proposal" => proposal,
+            "vote_limit" => vote_limit,
+            "limit_in_bps" => limit_in_bps,
+            "vote" => vote,
+            "amount" => amount,
+        },
+    );
+}
+
+#[no_mangle]
+pub extern "C" fn vote_by_pledges() {
+    let vote: bool = runtime::get_named_arg("vote");
+    let amount: U256 = runtime::get_named_arg("amount");
+    runtime::call_contract::<()>(
+        dao(),
+        "vote_by_pledges",
+        runtime_args! {"vote" => vote, "amount" => amount},
+    );
+}
+
+#[no_mangle]
+pub extern "C" fn claim() {
+    let id: u64 = runtime::get_named_arg("id");
+    runtime::call_contract::<()>(dao(), "claim", runtime_args! {"id" => id});
+}
+
+// pays with the purse of the contract
+#[no_mangle]
+pub extern "C" fn buy_tokens() {
+    runtime::call_contract::<()>(dao(), "buy_tokens", runtime_args! {"purse" => purse()});
+}
+
+#[no_mangle]
+pub extern "C" fn buy_on_curve() {
+    with_purse("buy_on_curve");
+}
+
+#[no_mangle]
+pub extern "C" fn sell_on_curve() {
+    with_purse("sell_on_curve");
+}
+
+#[no_mangle]
+pub extern "C" fn ragequit() {
+    with_purse("ragequit");
+}
+
+// saves what `quote_buy` or `quote_sell` returns as `voter_quote`
+#[no_mangle]
+pub extern "C" fn quote() {
+    let entry: String = runtime::get_named_arg("entry");
+    let amount: U256 = runtime::get_named_arg("amount");
+    let quote: U256 = runtime::call_contract(dao(), &entry, runtime_args! {"amount" => amount});
+    storage::write(
+        runtime::get_key("quote").unwrap().into_uref().unwrap(),
+        quote,
+    );
+}
+
+// saves what `balance_of` or `free_balance_of` returns as `voter_answer`
+#[no_mangle]
+pub extern "C" fn ask() {
+    let entry: String = runtime::get_named_arg("entry");
+    let account: Key = runtime::get_named_arg("account");
+    let balance: U256 = runtime::call_contract(dao(), &entry, runtime_args! {"account" => account});
+    storage::write(
+        runtime::get_key("answer").unwrap().into_uref().unwrap(),
+        balance,
+    );
+}
+
+// saves what `is_member` returns as `voter_member`
+#[no_mangle]
+pub extern "C" fn is_member() {
+    let account: Key = runtime::get_named_arg("account");
+    let member: bool =
+        runtime::call_contract(dao(), "is_member", runtime_args! {"account" => account});
+    storage::write(
+        runtime::get_key("member").unwrap().into_uref().unwrap(),
+        member,
+    );
+}
+
+// saves what `balance_of_at` returns as `voter_answer`
+#[no_mangle]
+pub extern "C" fn balance_of_at() {
+    let account: Key = runtime::get_named_arg("account");
+    let time: u64 = runtime::get_named_arg("time");
+    let balance: U256 = runtime::call_contract(
+        dao(),
+        "balance_of_at",
+        runtime_args! {"account" => account, "time" => time},
+    );
+    storage::write(
+        runtime::get_key("answer").unwrap().into_uref().unwrap(),
+        balance,
+    );
+}
+
+// pays and is paid with the purse of the contract
+fn with_purse(entry: &str) {
+    let amount: U256 = runtime::get_named_arg("amount");
+    runtime::call_contract::<()>(
+        dao(),
+        entry,
+        runtime_args! {"purse" => purse(), "amount" => amount},
+    );
+}
+
+fn dao() -> ContractHash {
+    storage::read(runtime::get_key("dao").unwrap().into_uref().unwrap())
+        .unwrap()
+        .unwrap()
+}
+
+fn purse() -> URef {
+    runtime::get_key("purse").unwrap().into_uref().unwrap()
+}